@@ -0,0 +1,385 @@
+//! Suffix array with the smallest index type which fits the word
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{any_array::*, tree::*};
+//!
+//! let word: &str = "Some word";
+//! let find: &str = "word";
+//!
+//! // construct suffix array, u8 indices are enough here
+//! let sa = AnySuffixArray::new(word);
+//! assert_eq!(sa.index_size(), 1);
+//!
+//! // construct lcp
+//! let lcp: AnyLCP = sa.lcp();
+//!
+//! // finds the entry position of the line 'find' in 'word'
+//! let res: Option<usize> = sa.find(find);
+//!
+//! // finds all the entry position of the line 'find' in 'word'
+//! let res_all: Vec<usize> = sa.find_all(find).collect();
+//!
+//! // convert suffix array to suffix tree
+//! let st = SuffixTree::from(sa);
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::{mem::size_of, slice::Iter};
+
+use crate::{
+    array::{build_suffix_array::ToUsize, *},
+    lcp::*,
+    tree::*,
+};
+
+#[derive(Debug, Clone)]
+pub enum AnySuffixArray<'sa> {
+    U8(SuffixArray<'sa, u8>),
+    U16(SuffixArray<'sa, u16>),
+    U32(SuffixArray<'sa, u32>),
+    Usize(SuffixArray<'sa, usize>),
+}
+
+macro_rules! dispatch {
+    ($self:expr, $sa:ident => $e:expr) => {
+        match $self {
+            AnySuffixArray::U8($sa) => $e,
+            AnySuffixArray::U16($sa) => $e,
+            AnySuffixArray::U32($sa) => $e,
+            AnySuffixArray::Usize($sa) => $e,
+        }
+    };
+}
+
+impl<'sa> AnySuffixArray<'sa> {
+    /// Construct suffix array with the smallest index type. Complexity O(n)
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let sa = AnySuffixArray::new("word");
+    /// assert!(matches!(sa, AnySuffixArray::U8(_)));
+    /// let word = "a".repeat(1000);
+    /// let sa = AnySuffixArray::new(&word);
+    /// assert!(matches!(sa, AnySuffixArray::U16(_)));
+    /// ```
+    /// At the end of the line should hit '\0'.
+    /// If there is no '\0' at the end then the line will be copied and added '\0' to the end.
+    /// Otherwise, the value will be taken by reference.
+    pub fn new(word: &'sa str) -> Self {
+        // the same bound as in SuffixArray::new
+        if word.len() < u8::MAX as usize {
+            AnySuffixArray::U8(SuffixArray::new(word))
+        } else if word.len() < u16::MAX as usize {
+            AnySuffixArray::U16(SuffixArray::new(word))
+        } else if word.len() < u32::MAX as usize {
+            AnySuffixArray::U32(SuffixArray::new(word))
+        } else {
+            AnySuffixArray::Usize(SuffixArray::new(word))
+        }
+    }
+
+    /// Construct suffix array over arbitrary bytes with the smallest index type. Complexity O(n)
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let sa = AnySuffixArray::from_bytes(&[0xff, 0x00, 0x7f]);
+    /// assert_eq!(sa.iter().collect::<Vec<_>>(), vec![3, 1, 2, 0]);
+    /// ```
+    /// The bytes will be copied and the sentinel 0 added to the end.
+    /// Zero bytes inside the word, including the last one, are allowed.
+    pub fn from_bytes(word: &'sa [u8]) -> Self {
+        // the same bound as in SuffixArray::from_bytes
+        if word.len() < u8::MAX as usize {
+            AnySuffixArray::U8(SuffixArray::from_bytes(word))
+        } else if word.len() < u16::MAX as usize {
+            AnySuffixArray::U16(SuffixArray::from_bytes(word))
+        } else if word.len() < u32::MAX as usize {
+            AnySuffixArray::U32(SuffixArray::from_bytes(word))
+        } else {
+            AnySuffixArray::Usize(SuffixArray::from_bytes(word))
+        }
+    }
+
+    /// Return size of index type in bytes
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// assert_eq!(AnySuffixArray::new("word").index_size(), 1);
+    /// ```
+    #[inline]
+    pub fn index_size(&self) -> usize {
+        match self {
+            AnySuffixArray::U8(_) => size_of::<u8>(),
+            AnySuffixArray::U16(_) => size_of::<u16>(),
+            AnySuffixArray::U32(_) => size_of::<u32>(),
+            AnySuffixArray::Usize(_) => size_of::<usize>(),
+        }
+    }
+
+    /// Return iterator on suffix array
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// AnySuffixArray::new("word").iter().for_each(|idx| println!("idx: {}", idx));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> AnyIter<'_> {
+        dispatch!(self, sa => AnyIter::from(&sa.suffix_array()[..]))
+    }
+
+    /// Return ref on word
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let sa = AnySuffixArray::new("word");
+    /// assert_eq!("word\0", sa.word());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the word is not valid UTF-8.
+    #[inline]
+    pub fn word(&self) -> &str {
+        dispatch!(self, sa => sa.word())
+    }
+
+    /// Return ref on word as bytes
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let sa = AnySuffixArray::from_bytes(&[0xff, 0x00, 0x7f]);
+    /// assert_eq!(&[0xff, 0x00, 0x7f, 0x00], sa.word_bytes());
+    /// ```
+    #[inline]
+    pub fn word_bytes(&self) -> &[u8] {
+        dispatch!(self, sa => sa.word_bytes())
+    }
+
+    /// Split suffix array, indices are widened to usize
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let (word, sa) = AnySuffixArray::new("word").split_owned_bytes();
+    /// ```
+    pub fn split_owned_bytes(self) -> (Cow<'sa, [u8]>, Vec<usize>) {
+        dispatch!(self, sa => {
+            let (word, sa) = sa.split_owned_bytes();
+            (word, sa.into_iter().map(|x| x.to_usize()).collect())
+        })
+    }
+
+    /// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+    /// Construct LCP with the same index type. Complexity O(n)
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let lcp = AnySuffixArray::new("abab").lcp();
+    /// assert_eq!(lcp.iter().collect::<Vec<_>>(), vec![0, 0, 2, 0, 1]);
+    /// ```
+    pub fn lcp(&self) -> AnyLCP {
+        match self {
+            AnySuffixArray::U8(sa) => AnyLCP::U8(sa.lcp()),
+            AnySuffixArray::U16(sa) => AnyLCP::U16(sa.lcp()),
+            AnySuffixArray::U32(sa) => AnyLCP::U32(sa.lcp()),
+            AnySuffixArray::Usize(sa) => AnyLCP::Usize(sa.lcp()),
+        }
+    }
+
+    /// Find substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let find: Option<usize> = AnySuffixArray::new("word").find("or");
+    /// assert_eq!(find, Some(1));
+    /// ```
+    #[inline]
+    pub fn find<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Option<usize> {
+        dispatch!(self, sa => sa.find(find).map(|x| x.to_usize()))
+    }
+
+    /// Find all substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let sa = AnySuffixArray::new("abab");
+    /// let find: Vec<usize> = sa.find_all("ab").collect();
+    /// assert_eq!(find, vec![2, 0]);
+    /// ```
+    #[inline]
+    pub fn find_all<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> AnyIter<'_> {
+        dispatch!(self, sa => AnyIter::from(sa.find_all(find)))
+    }
+
+    /// Number of entries of substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// assert_eq!(AnySuffixArray::new("abab").count("ab"), 2);
+    /// ```
+    #[inline]
+    pub fn count<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> usize {
+        dispatch!(self, sa => sa.count(find))
+    }
+}
+
+impl From<SuffixTree<'_>> for AnySuffixArray<'_> {
+    /// Construct suffix array from suffix tree with the smallest index type. Complexity O(n)
+    /// ```
+    /// use suff_collections::{any_array::*, tree::*};
+    ///
+    /// let sa = AnySuffixArray::from(SuffixTree::new("word"));
+    /// ```
+    fn from(tree: SuffixTree) -> Self {
+        let len = tree.word().len();
+        if len < u8::MAX as usize {
+            AnySuffixArray::U8(SuffixArray::from(tree))
+        } else if len < u16::MAX as usize {
+            AnySuffixArray::U16(SuffixArray::from(tree))
+        } else if len < u32::MAX as usize {
+            AnySuffixArray::U32(SuffixArray::from(tree))
+        } else {
+            AnySuffixArray::Usize(SuffixArray::from(tree))
+        }
+    }
+}
+
+impl From<AnySuffixArray<'_>> for SuffixTree<'_> {
+    /// Construct suffix tree from suffix array. Complexity O(n)
+    /// ```
+    /// use suff_collections::{any_array::*, tree::*};
+    ///
+    /// let st = SuffixTree::from(AnySuffixArray::new("word"));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the word of suffix array is not valid UTF-8.
+    fn from(array: AnySuffixArray) -> Self {
+        dispatch!(array, sa => SuffixTree::from(sa))
+    }
+}
+
+/// LCP with the index type of `AnySuffixArray`
+#[derive(Debug, Clone)]
+pub enum AnyLCP {
+    U8(LCP<u8>),
+    U16(LCP<u16>),
+    U32(LCP<u32>),
+    Usize(LCP<usize>),
+}
+
+impl AnyLCP {
+    /// Return iterator on lcp
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let lcp = AnySuffixArray::new("word").lcp();
+    /// lcp.iter().for_each(|x| println!("lcp: {}", x));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> AnyIter<'_> {
+        match self {
+            AnyLCP::U8(lcp) => AnyIter::from(lcp.inner()),
+            AnyLCP::U16(lcp) => AnyIter::from(lcp.inner()),
+            AnyLCP::U32(lcp) => AnyIter::from(lcp.inner()),
+            AnyLCP::Usize(lcp) => AnyIter::from(lcp.inner()),
+        }
+    }
+
+    /// Return lcp\[idx\] or None if idx is out of bounds
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// let lcp = AnySuffixArray::new("abab").lcp();
+    /// assert_eq!(lcp.get(2), Some(2));
+    /// assert_eq!(lcp.get(5), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<usize> {
+        self.iter().nth(idx)
+    }
+
+    /// Return len of lcp
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// assert_eq!(AnySuffixArray::new("word").lcp().len(), 5);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.iter().len()
+    }
+
+    /// Check if lcp is empty
+    /// ```
+    /// use suff_collections::any_array::*;
+    ///
+    /// assert!(AnySuffixArray::new("").lcp().is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Iterator over indices of any width as usize
+#[derive(Debug, Clone)]
+pub enum AnyIter<'a> {
+    U8(Iter<'a, u8>),
+    U16(Iter<'a, u16>),
+    U32(Iter<'a, u32>),
+    Usize(Iter<'a, usize>),
+}
+
+macro_rules! impl_AnyIter_from {
+    ($($tp:ident => $variant:ident),* $(,)?) => {
+        $(
+            impl<'a> From<&'a [$tp]> for AnyIter<'a> {
+                #[inline]
+                fn from(x: &'a [$tp]) -> Self {
+                    AnyIter::$variant(x.iter())
+                }
+            }
+        )*
+    }
+}
+impl_AnyIter_from!(u8 => U8, u16 => U16, u32 => U32, usize => Usize);
+
+impl Iterator for AnyIter<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            AnyIter::U8(x) => x.next().map(|&x| x.to_usize()),
+            AnyIter::U16(x) => x.next().map(|&x| x.to_usize()),
+            AnyIter::U32(x) => x.next().map(|&x| x.to_usize()),
+            AnyIter::Usize(x) => x.next().copied(),
+        }
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match self {
+            AnyIter::U8(x) => x.nth(n).map(|&x| x.to_usize()),
+            AnyIter::U16(x) => x.nth(n).map(|&x| x.to_usize()),
+            AnyIter::U32(x) => x.nth(n).map(|&x| x.to_usize()),
+            AnyIter::Usize(x) => x.nth(n).copied(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            AnyIter::U8(x) => x.size_hint(),
+            AnyIter::U16(x) => x.size_hint(),
+            AnyIter::U32(x) => x.size_hint(),
+            AnyIter::Usize(x) => x.size_hint(),
+        }
+    }
+}
+
+impl ExactSizeIterator for AnyIter<'_> {}
@@ -39,22 +39,42 @@
 //!
 //! // convert suffix array to suffix tree
 //! let st = SuffixTree::from(sa);
+//!
+//! // construct suffix array over arbitrary bytes
+//! // let sa = SuffixArray::<usize>::from_bytes_stack(bytes);
+//! let bytes: &[u8] = &[0xff, 0x00, 0x7f];
+//! let sa = SuffixArray::<usize>::from_bytes(bytes);
+//! let res_all: &[usize] = sa.find_all(&[0x00, 0x7f]);
 //! ```
 
-use alloc::borrow::{Cow, ToOwned};
+use alloc::borrow::Cow;
+use alloc::string::String;
 use alloc::vec::{IntoIter, Vec};
 use core::{
     cmp::{max, Eq},
+    iter,
+    ops::Range,
     option::Option,
     slice::Iter,
     str,
 };
 
-use crate::{bit::*, canonic_word, lcp::*, tree::*};
-use build_suffix_array::{Max, SuffixIndices};
+use crate::{
+    bit::*,
+    bwt::bwt,
+    canonic_word,
+    error::SuffixError,
+    inverse_array::InverseSuffixArray,
+    lcp::*,
+    lz77::{lz77_factorize, Factor},
+    repeats::*,
+    tree::*,
+    with_sentinel,
+};
+use build_suffix_array::{Layout, Max, SuffixIndices, ToUsize};
 
 #[repr(transparent)]
-struct ByteSliceMut<'t>(&'t mut [Byte]);
+pub(crate) struct ByteSliceMut<'t>(&'t mut [Byte]);
 
 impl<'t> BitMut for ByteSliceMut<'t> {
     #[inline]
@@ -85,7 +105,7 @@ impl<'t> Bit for ByteSliceMut<'t> {
 }
 
 #[repr(transparent)]
-struct ByteSlice<'t>(&'t [Byte]);
+pub(crate) struct ByteSlice<'t>(&'t [Byte]);
 impl<'t> Bit for ByteSlice<'t> {
     #[inline]
     unsafe fn raw(&self) -> &[Byte] {
@@ -97,12 +117,118 @@ impl<'t> Bit for ByteSlice<'t> {
     }
 }
 
+/// Interval of the suffix array with all suffixes which start with the same pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaRange {
+    start: usize,
+    end: usize,
+    pattern_len: usize,
+}
+
+impl SaRange {
+    /// Return index of the first suffix in the suffix array
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert_eq!(sa.range("b").start(), 3);
+    /// ```
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Return index after the last suffix in the suffix array
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert_eq!(sa.range("b").end(), 5);
+    /// ```
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Return length of the pattern
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert_eq!(sa.range("ab").pattern_len(), 2);
+    /// ```
+    #[inline]
+    pub fn pattern_len(&self) -> usize {
+        self.pattern_len
+    }
+
+    /// Number of entries of the pattern
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert_eq!(sa.range("ab").count(), 2);
+    /// ```
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Check if the pattern has no entries
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert!(sa.range("ba").is_empty() == false);
+    /// assert!(sa.range("bb").is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Return interval as range of indices in the suffix array
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert_eq!(sa.range("b").as_range(), 3..5);
+    /// ```
+    #[inline]
+    pub fn as_range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SuffixArray<'sa, T: SuffixIndices<T>> {
-    word: Cow<'sa, str>,
+    word: Cow<'sa, [u8]>,
     sa: Vec<T>,
 }
 
+#[cfg(feature = "serde")]
+impl<'de, T: SuffixIndices<T> + serde::Deserialize<'de>> serde::Deserialize<'de>
+    for SuffixArray<'_, T>
+{
+    /// Deserialize suffix array and check it. Complexity O(n)
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "SuffixArray")]
+        struct Raw<T> {
+            word: Vec<u8>,
+            sa: Vec<T>,
+        }
+
+        let raw = Raw::<T>::deserialize(deserializer)?;
+        check_suffix_array(&raw.word, &raw.sa).map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            word: Cow::from(raw.word),
+            sa: raw.sa,
+        })
+    }
+}
+
 impl<'sa, T: SuffixIndices<T>> IntoIterator for SuffixArray<'sa, T> {
     type Item = T;
     type IntoIter = IntoIter<Self::Item>;
@@ -143,45 +269,7 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     ///
     /// This function will panic if word.len() > T::MAX.
     pub fn new_compress(word: &'sa str) -> Self {
-        assert!(word.len() < <T as Max>::max().to_usize());
-        if word.is_empty() {
-            return Self {
-                word: Cow::from(""),
-                sa: vec![],
-            };
-        }
-        let word = canonic_word(word);
-        let mut offset_dict =
-            vec![(T::zero(), T::zero()); max(word.len(), SuffixArray::<T>::DICT_SIZE)];
-        let mut tmp_end_s = vec![T::zero(); offset_dict.len()];
-        let mut sa = vec![T::zero(); word.len()];
-        let mut sa_init = Byte::vec(word.len());
-        // safe because
-        //      offset_dict.len() > max(s_idx) && offset_dict.len() >= s_idx.len()
-        //      tmp_end_s.len() == offset_dict.len()
-        //      sa.len() == s_idx.len()
-        //      sa_init.len() == sa.len()
-        //      s_idx.len() == word.len()
-        //      word.last() == '\0'
-        debug_assert!(
-            offset_dict.len() > *word.as_bytes().iter().max().unwrap() as usize
-                && offset_dict.len() >= word.as_bytes().len()
-                && tmp_end_s.len() == offset_dict.len()
-                && sa.len() == word.as_bytes().len()
-                && sa_init.len() * 8 >= sa.len()
-                && *word.as_bytes().last().unwrap() == 0
-        );
-        unsafe {
-            build_suffix_array::suffix_array(
-                word.as_bytes(),
-                &mut offset_dict,
-                &mut tmp_end_s,
-                &mut sa,
-                &mut BitArrMut(&mut sa_init),
-            );
-        }
-
-        Self { word, sa }
+        Self::build::<BitArrMut>(word.as_bytes(), canonic_word, Builder::Rec)
     }
 
     /// Construct suffix array not recursive. Complexity O(n).
@@ -203,44 +291,7 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     ///
     /// This function will panic if word.len() > T::MAX.
     pub fn new_stack_compress(word: &'sa str) -> Self {
-        assert!(word.len() < <T as Max>::max().to_usize());
-        if word.is_empty() {
-            return Self {
-                word: Cow::from(""),
-                sa: vec![],
-            };
-        }
-        let word = canonic_word(word);
-        let mut offset_dict =
-            vec![(T::zero(), T::zero()); max(word.len(), SuffixArray::<T>::DICT_SIZE)];
-        let mut tmp_end_s = vec![T::zero(); offset_dict.len()];
-        let mut sa = vec![T::zero(); word.len()];
-        let mut sa_init = Byte::vec(word.len());
-        // safe because
-        //      offset_dict.len() > max(s_idx) && offset_dict.len() >= s_idx.len()
-        //      tmp_end_s.len() == offset_dict.len()
-        //      sa.len() == s_idx.len()
-        //      s_idx.len() == word.len()
-        //      word.last() == '\0'
-        debug_assert!(
-            offset_dict.len() > *word.as_bytes().iter().max().unwrap() as usize
-                && offset_dict.len() >= word.as_bytes().len()
-                && tmp_end_s.len() == offset_dict.len()
-                && sa.len() == word.as_bytes().len()
-                && sa_init.len() * 8 >= sa.len()
-                && *word.as_bytes().last().unwrap() == 0
-        );
-        unsafe {
-            build_suffix_array::suffix_array_stack(
-                word.as_bytes(),
-                &mut offset_dict,
-                &mut tmp_end_s,
-                &mut sa,
-                &mut BitArrMut(&mut sa_init),
-            );
-        }
-
-        Self { word, sa }
+        Self::build::<BitArrMut>(word.as_bytes(), canonic_word, Builder::Stack)
     }
 
     /// Construct suffix array recursive. Complexity O(n)
@@ -261,45 +312,7 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     ///
     /// This function will panic if word.len() > T::MAX.
     pub fn new(word: &'sa str) -> Self {
-        assert!(word.len() < <T as Max>::max().to_usize());
-        if word.is_empty() {
-            return Self {
-                word: Cow::from(""),
-                sa: vec![],
-            };
-        }
-        let word = canonic_word(word);
-        let mut offset_dict =
-            vec![(T::zero(), T::zero()); max(word.len(), SuffixArray::<T>::DICT_SIZE)];
-        let mut tmp_end_s = vec![T::zero(); offset_dict.len()];
-        let mut sa = vec![T::zero(); word.len()];
-        let mut sa_init = vec![Byte::default(); word.len()];
-        // safe because
-        //      offset_dict.len() > max(s_idx) && offset_dict.len() >= s_idx.len()
-        //      tmp_end_s.len() == offset_dict.len()
-        //      sa.len() == s_idx.len()
-        //      sa_init.len() == sa.len()
-        //      s_idx.len() == word.len()
-        //      word.last() == '\0'
-        debug_assert!(
-            offset_dict.len() > *word.as_bytes().iter().max().unwrap() as usize
-                && offset_dict.len() >= word.as_bytes().len()
-                && tmp_end_s.len() == offset_dict.len()
-                && sa.len() == word.as_bytes().len()
-                && sa_init.len() == sa.len()
-                && *word.as_bytes().last().unwrap() == 0
-        );
-        unsafe {
-            build_suffix_array::suffix_array(
-                word.as_bytes(),
-                &mut offset_dict,
-                &mut tmp_end_s,
-                &mut sa,
-                &mut ByteSliceMut(&mut sa_init),
-            );
-        }
-
-        Self { word, sa }
+        Self::build::<ByteSliceMut>(word.as_bytes(), canonic_word, Builder::Rec)
     }
 
     /// Construct suffix array not recursive. Complexity O(n)
@@ -320,43 +333,285 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     ///
     /// This function will panic if word.len() > T::MAX.
     pub fn new_stack(word: &'sa str) -> Self {
+        Self::build::<ByteSliceMut>(word.as_bytes(), canonic_word, Builder::Stack)
+    }
+
+    /// Construct suffix array over arbitrary bytes recursive. Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// // let sa = SuffixArray::<u8>::from_bytes(&[0xff, 0x00, 0x7f]);
+    /// // let sa = SuffixArray::<u16>::from_bytes(&[0xff, 0x00, 0x7f]);
+    /// // let sa = SuffixArray::<u32>::from_bytes(&[0xff, 0x00, 0x7f]);
+    /// let sa = SuffixArray::<usize>::from_bytes(&[0xff, 0x00, 0x7f]);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// The bytes will be copied and the sentinel 0 added to the end.
+    /// Zero bytes inside the word, including the last one, are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX.
+    pub fn from_bytes(word: &'sa [u8]) -> Self {
+        Self::build::<ByteSliceMut>(word, with_sentinel, Builder::Rec)
+    }
+
+    /// Construct suffix array over arbitrary bytes recursive. Complexity O(n).
+    /// Uses less memory to build than `from_bytes` by using bitpcking.
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::from_bytes_compress(&[0xff, 0x00, 0x7f]);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// The bytes will be copied and the sentinel 0 added to the end.
+    /// Zero bytes inside the word, including the last one, are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX.
+    pub fn from_bytes_compress(word: &'sa [u8]) -> Self {
+        Self::build::<BitArrMut>(word, with_sentinel, Builder::Rec)
+    }
+
+    /// Construct suffix array over arbitrary bytes not recursive. Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::from_bytes_stack(&[0xff, 0x00, 0x7f]);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// The bytes will be copied and the sentinel 0 added to the end.
+    /// Zero bytes inside the word, including the last one, are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX.
+    pub fn from_bytes_stack(word: &'sa [u8]) -> Self {
+        Self::build::<ByteSliceMut>(word, with_sentinel, Builder::Stack)
+    }
+
+    /// Construct suffix array over arbitrary bytes not recursive. Complexity O(n).
+    /// Uses less memory to build than `from_bytes_stack` by using bitpcking.
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::from_bytes_stack_compress(&[0xff, 0x00, 0x7f]);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// The bytes will be copied and the sentinel 0 added to the end.
+    /// Zero bytes inside the word, including the last one, are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX.
+    pub fn from_bytes_stack_compress(word: &'sa [u8]) -> Self {
+        Self::build::<BitArrMut>(word, with_sentinel, Builder::Stack)
+    }
+
+    /// Construct suffix array in parallel. Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new_par("word");
+    /// assert_eq!(sa.suffix_array(), SuffixArray::<usize>::new("word").suffix_array());
+    /// ```
+    /// Requires feature `rayon`. The result is the same as of `new`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX.
+    #[cfg(feature = "rayon")]
+    pub fn new_par(word: &'sa str) -> Self {
+        Self::build::<ByteSliceMut>(word.as_bytes(), canonic_word, Builder::Par)
+    }
+
+    /// Construct suffix array over arbitrary bytes in parallel. Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::from_bytes_par(&[0xff, 0x00, 0x7f]);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// Requires feature `rayon`. The result is the same as of `from_bytes`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX.
+    #[cfg(feature = "rayon")]
+    pub fn from_bytes_par(word: &'sa [u8]) -> Self {
+        Self::build::<ByteSliceMut>(word, with_sentinel, Builder::Par)
+    }
+
+    /// Construct suffix array recursive without panic. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_new("word");
+    /// assert!(sa.is_ok());
+    /// let word = "a".repeat(255);
+    /// let sa = SuffixArray::<u8>::try_new(&word);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::IndexTooSmall { len: 255, max: 255 });
+    /// let sa = SuffixArray::<u8>::try_new("");
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `new`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_new(word: &'sa str) -> Result<Self, SuffixError> {
+        Self::try_build::<ByteSliceMut>(word.as_bytes(), canonic_word, Builder::Rec)
+    }
+
+    /// Construct suffix array recursive without panic. Complexity O(n).
+    /// Uses less memory to build than `try_new` by using bitpcking
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_new_compress("word");
+    /// assert!(sa.is_ok());
+    /// let word = "a".repeat(255);
+    /// let sa = SuffixArray::<u8>::try_new_compress(&word);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::IndexTooSmall { len: 255, max: 255 });
+    /// let sa = SuffixArray::<u8>::try_new_compress("");
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `new_compress`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_new_compress(word: &'sa str) -> Result<Self, SuffixError> {
+        Self::try_build::<BitArrMut>(word.as_bytes(), canonic_word, Builder::Rec)
+    }
+
+    /// Construct suffix array not recursive without panic. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_new_stack("word");
+    /// assert!(sa.is_ok());
+    /// let word = "a".repeat(255);
+    /// let sa = SuffixArray::<u8>::try_new_stack(&word);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::IndexTooSmall { len: 255, max: 255 });
+    /// let sa = SuffixArray::<u8>::try_new_stack("");
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `new_stack`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_new_stack(word: &'sa str) -> Result<Self, SuffixError> {
+        Self::try_build::<ByteSliceMut>(word.as_bytes(), canonic_word, Builder::Stack)
+    }
+
+    /// Construct suffix array not recursive without panic. Complexity O(n).
+    /// Uses less memory to build than `try_new_stack` by using bitpcking
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_new_stack_compress("word");
+    /// assert!(sa.is_ok());
+    /// let word = "a".repeat(255);
+    /// let sa = SuffixArray::<u8>::try_new_stack_compress(&word);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::IndexTooSmall { len: 255, max: 255 });
+    /// let sa = SuffixArray::<u8>::try_new_stack_compress("");
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `new_stack_compress`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_new_stack_compress(word: &'sa str) -> Result<Self, SuffixError> {
+        Self::try_build::<BitArrMut>(word.as_bytes(), canonic_word, Builder::Stack)
+    }
+
+    /// Construct suffix array over arbitrary bytes recursive without panic. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_from_bytes(&[0xff, 0x00, 0x7f]);
+    /// assert!(sa.is_ok());
+    /// let sa = SuffixArray::<u8>::try_from_bytes(&[]);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `from_bytes`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_from_bytes(word: &'sa [u8]) -> Result<Self, SuffixError> {
+        Self::try_build::<ByteSliceMut>(word, with_sentinel, Builder::Rec)
+    }
+
+    /// Construct suffix array over arbitrary bytes recursive without panic. Complexity O(n).
+    /// Uses less memory to build than `try_from_bytes` by using bitpcking
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_from_bytes_compress(&[0xff, 0x00, 0x7f]);
+    /// assert!(sa.is_ok());
+    /// let sa = SuffixArray::<u8>::try_from_bytes_compress(&[]);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `from_bytes_compress`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_from_bytes_compress(word: &'sa [u8]) -> Result<Self, SuffixError> {
+        Self::try_build::<BitArrMut>(word, with_sentinel, Builder::Rec)
+    }
+
+    /// Construct suffix array over arbitrary bytes not recursive without panic. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_from_bytes_stack(&[0xff, 0x00, 0x7f]);
+    /// assert!(sa.is_ok());
+    /// let sa = SuffixArray::<u8>::try_from_bytes_stack(&[]);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `from_bytes_stack`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_from_bytes_stack(word: &'sa [u8]) -> Result<Self, SuffixError> {
+        Self::try_build::<ByteSliceMut>(word, with_sentinel, Builder::Stack)
+    }
+
+    /// Construct suffix array over arbitrary bytes not recursive without panic. Complexity O(n).
+    /// Uses less memory to build than `try_from_bytes_stack` by using bitpcking
+    /// ```
+    /// use suff_collections::{array::*, error::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_from_bytes_stack_compress(&[0xff, 0x00, 0x7f]);
+    /// assert!(sa.is_ok());
+    /// let sa = SuffixArray::<u8>::try_from_bytes_stack_compress(&[]);
+    /// assert_eq!(sa.unwrap_err(), SuffixError::EmptyInput);
+    /// ```
+    /// Same as `from_bytes_stack_compress`, but return error if the word is empty or word.len() >= T::MAX.
+    pub fn try_from_bytes_stack_compress(word: &'sa [u8]) -> Result<Self, SuffixError> {
+        Self::try_build::<BitArrMut>(word, with_sentinel, Builder::Stack)
+    }
+
+    fn try_build<BitLayout: Layout>(
+        word: &'sa [u8],
+        sentinel: fn(&'sa [u8]) -> Cow<'sa, [u8]>,
+        builder: Builder,
+    ) -> Result<Self, SuffixError> {
+        if word.is_empty() {
+            return Err(SuffixError::EmptyInput);
+        }
+        check_index_size::<T>(word.len())?;
+        Ok(Self::build::<BitLayout>(word, sentinel, builder))
+    }
+
+    // sentinel adds 0 to the end of the word: canonic_word for str,
+    // with_sentinel for bytes whose trailing 0 is a symbol of the word
+    fn build<BitLayout: Layout>(
+        word: &'sa [u8],
+        sentinel: fn(&'sa [u8]) -> Cow<'sa, [u8]>,
+        builder: Builder,
+    ) -> Self {
         assert!(word.len() < <T as Max>::max().to_usize());
         if word.is_empty() {
             return Self {
-                word: Cow::from(""),
+                word: Cow::from(&[][..]),
                 sa: vec![],
             };
         }
-        let word = canonic_word(word);
-        let mut offset_dict =
-            vec![(T::zero(), T::zero()); max(word.len(), SuffixArray::<T>::DICT_SIZE)];
-        let mut tmp_end_s = vec![T::zero(); offset_dict.len()];
-        let mut sa = vec![T::zero(); word.len()];
-        let mut sa_init = vec![Byte::default(); word.len()];
-        // safe because
-        //      offset_dict.len() > max(s_idx) && offset_dict.len() >= s_idx.len()
-        //      tmp_end_s.len() == offset_dict.len()
-        //      sa.len() == s_idx.len()
-        //      sa_init.len() == sa.len()
-        //      s_idx.len() == word.len()
-        //      word.last() == '\0'
-        debug_assert!(
-            offset_dict.len() > *word.as_bytes().iter().max().unwrap() as usize
-                && offset_dict.len() >= word.as_bytes().len()
-                && tmp_end_s.len() == offset_dict.len()
-                && sa.len() == word.as_bytes().len()
-                && sa_init.len() == sa.len()
-                && *word.as_bytes().last().unwrap() == 0
-        );
-        unsafe {
-            build_suffix_array::suffix_array_stack(
-                word.as_bytes(),
-                &mut offset_dict,
-                &mut tmp_end_s,
-                &mut sa,
-                &mut ByteSliceMut(&mut sa_init),
-            );
-        }
+        let word = sentinel(word);
+        let sa = if word[..word.len() - 1].contains(&0) {
+            // SA-IS needs a unique smallest sentinel. Shift every byte
+            // by one so that zeros inside the word stay greater than the sentinel.
+            // The order of suffixes does not change because the sentinel
+            // is the last symbol of the word.
+            let s_idx = word[..word.len() - 1]
+                .iter()
+                .map(|&x| x as u16 + 1)
+                .chain(iter::once(0))
+                .collect::<Vec<_>>();
+            sort_suffixes::<_, T, BitLayout>(&s_idx, SuffixArray::<T>::DICT_SIZE + 1, builder)
+        } else {
+            sort_suffixes::<_, T, BitLayout>(&word, SuffixArray::<T>::DICT_SIZE, builder)
+        };
 
         Self { word, sa }
     }
@@ -378,8 +633,28 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     ///
     /// let (word, sa) = SuffixArray::<usize>::new("word").split_owned();
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the word is not valid UTF-8.
     #[inline]
     pub fn split_owned(self) -> (Cow<'sa, str>, Vec<T>) {
+        let word = match self.word {
+            Cow::Borrowed(x) => Cow::from(str::from_utf8(x).unwrap()),
+            Cow::Owned(x) => Cow::from(String::from_utf8(x).unwrap()),
+        };
+        (word, self.sa)
+    }
+
+    /// Split suffix array built over bytes
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let (word, sa) = SuffixArray::<usize>::from_bytes(&[0xff, 0x00]).split_owned_bytes();
+    /// assert_eq!(&word[..], &[0xff, 0x00, 0x00]);
+    /// ```
+    #[inline]
+    pub fn split_owned_bytes(self) -> (Cow<'sa, [u8]>, Vec<T>) {
         (self.word, self.sa)
     }
 
@@ -403,8 +678,26 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     /// let word: &str = sa.word();
     /// assert_eq!("word\0", word);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the word is not valid UTF-8.
+    /// Use `word_bytes` for suffix arrays built over arbitrary bytes.
     #[inline]
     pub fn word(&self) -> &str {
+        str::from_utf8(&self.word).unwrap()
+    }
+
+    /// Return ref on word bytes
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::from_bytes(&[0xff, 0x00, 0x7f]);
+    /// let word: &[u8] = sa.word_bytes();
+    /// assert_eq!(&[0xff, 0x00, 0x7f, 0x00], word);
+    /// ```
+    #[inline]
+    pub fn word_bytes(&self) -> &[u8] {
         &self.word
     }
 
@@ -416,43 +709,91 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     /// let lcp = SuffixArray::<usize>::new("word").lcp();
     /// ```
     pub fn lcp(&self) -> LCP<T> {
-        let mut lcp = LCP::<T>::new(vec![T::zero(); self.sa.len()]);
-        let mut sa_idx = vec![T::zero(); self.sa.len()];
+        lcp_kasai(self.text(), &self.sa)
+    }
 
-        // safe max(sa) < sa_idx.len()
-        self.sa.iter().enumerate().for_each(|(i, &x)| unsafe {
-            *sa_idx.get_unchecked_mut(x.to_usize()) = T::try_from(i + 1).ok().unwrap()
-        });
+    /// Inverse suffix array (rank of suffixes). Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, inverse_array::*};
+    ///
+    /// let sa = SuffixArray::<usize>::new("word");
+    /// let inverse: InverseSuffixArray<usize> = sa.inverse();
+    /// assert_eq!(inverse.rank(1), 2);
+    /// ```
+    pub fn inverse(&self) -> InverseSuffixArray<T> {
+        InverseSuffixArray::new(rank(&self.sa))
+    }
 
-        let mut pref_len = T::zero();
-        let word = self.word.as_bytes();
-        for x in sa_idx {
-            if x.to_usize() == self.sa.len() {
-                pref_len = T::zero();
-                continue;
-            }
+    /// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+    /// Construct LCP with the inverse suffix array computed before. Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("word");
+    /// let inverse = sa.inverse();
+    /// assert_eq!(sa.lcp_with_inverse(&inverse).inner(), sa.lcp().inner());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if inverse.len() != suffix array len.
+    pub fn lcp_with_inverse(&self, inverse: &InverseSuffixArray<T>) -> LCP<T> {
+        assert_eq!(inverse.len(), self.sa.len());
+        lcp_kasai_with_rank(self.text(), &self.sa, inverse.inner())
+    }
 
-            // safe max(sa_idx) < sa.len() && x < sa.len() by previous check
-            // safe l < word.len() && r < word.len()
-            let l = unsafe { *self.sa.get_unchecked(x.to_usize() - 1) };
-            let r = unsafe { *self.sa.get_unchecked(x.to_usize()) };
-            pref_len = unsafe {
-                count_eq(
-                    word.get_unchecked(l.to_usize()..),
-                    word.get_unchecked(r.to_usize()..),
-                    pref_len,
-                )
-            };
+    /// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+    /// Construct LCP through the permuted lcp (Φ array). Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abracadabra");
+    /// assert_eq!(sa.lcp_phi().inner(), sa.lcp().inner());
+    /// ```
+    /// The word is scanned in text order, so it is more cache-friendly than `lcp` on large words.
+    pub fn lcp_phi(&self) -> LCP<T> {
+        lcp_phi(self.text(), &self.sa)
+    }
 
-            // safe x < sa.len() by previous check && lcp.len() == sa.len()
-            unsafe {
-                *lcp.idx_mut(x.to_usize()) = pref_len;
-            }
-            if pref_len > T::zero() {
-                pref_len -= T::one();
-            }
-        }
-        lcp
+    /// Construct LCP through the permuted lcp (Φ array) in parallel. Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abracadabra");
+    /// assert_eq!(sa.lcp_phi_par().inner(), sa.lcp().inner());
+    /// ```
+    /// Requires feature `rayon`. The word is split into chunks which are processed in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn lcp_phi_par(&self) -> LCP<T> {
+        LCP::new(crate::par_build::lcp_phi(self.text(), &self.sa))
+    }
+
+    /// Burrows–Wheeler transform. Return bwt with the sentinel and primary index
+    /// (index of the sentinel in bwt). Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, bwt::*};
+    ///
+    /// let sa = SuffixArray::<usize>::new("banana");
+    /// let (bwt, primary) = sa.bwt();
+    /// assert_eq!(bwt, b"annb\0aa");
+    /// assert_eq!(primary, 4);
+    /// assert_eq!(inverse_bwt(&bwt, primary), sa.word_bytes());
+    /// ```
+    pub fn bwt(&self) -> (Vec<u8>, usize) {
+        bwt(&self.word, &self.sa)
+    }
+
+    /// LZ77 factorization of the word without the sentinel. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, lz77::*};
+    ///
+    /// let sa = SuffixArray::<usize>::new("aaaa");
+    /// let factors = sa.lz77_factorize();
+    /// assert_eq!(factors, vec![Factor::Literal(b'a'), Factor::Copy { source: 0, len: 3 }]);
+    /// assert_eq!(lz77_decode(&factors), b"aaaa");
+    /// ```
+    pub fn lz77_factorize(&self) -> Vec<Factor> {
+        lz77_factorize(self.text(), &self.sa)
     }
 
     /// Find substr. Complexity O(|find| * log(|word|))
@@ -464,9 +805,12 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     /// // let find: Option<u32> = SuffixArray::<u32>::new("word").find("or");
     /// let find: Option<usize> = SuffixArray::<usize>::new("word").find("or");
     /// assert_eq!(find, Some(1));
+    ///
+    /// let find: Option<usize> = SuffixArray::<usize>::from_bytes(&[0xff, 0x00, 0x7f]).find(&[0x00, 0x7f]);
+    /// assert_eq!(find, Some(1));
     /// ```
     #[inline]
-    pub fn find(&self, find: &str) -> Option<T> {
+    pub fn find<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Option<T> {
         let (start, end) = self.find_pos(find);
         if start >= end {
             return None;
@@ -481,13 +825,117 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     /// let sa = SuffixArray::<usize>::new("word");
     /// let find: &[usize] = sa.find_all("or");
     /// assert_eq!(find, &[1]);
+    ///
+    /// let sa = SuffixArray::<usize>::from_bytes(&[0x00, 0xff, 0x00, 0xff]);
+    /// let find: &[usize] = sa.find_all(&[0x00, 0xff]);
+    /// assert_eq!(find, &[2, 0]);
     /// ```
     #[inline]
-    pub fn find_all(&self, find: &str) -> &[T] {
+    pub fn find_all<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> &[T] {
         let (start, end) = self.find_pos(find);
         &self.sa[start..end]
     }
 
+    /// Number of entries of substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abracadabra");
+    /// assert_eq!(sa.count("abra"), 2);
+    /// assert_eq!(sa.count("abc"), 0);
+    /// ```
+    #[inline]
+    pub fn count<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> usize {
+        let (start, end) = self.find_pos(find);
+        end - start
+    }
+
+    /// Interval of all suffixes which start with substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abracadabra");
+    /// let range: SaRange = sa.range("abra");
+    /// assert_eq!(range.count(), 2);
+    /// assert_eq!(sa.positions(range), sa.find_all("abra"));
+    /// ```
+    /// The empty substr has no entries as in `count` and `find_all`,
+    /// but its range can still be extended.
+    #[inline]
+    pub fn range<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> SaRange {
+        self.extend(
+            SaRange {
+                start: 0,
+                end: 0,
+                pattern_len: 0,
+            },
+            find,
+        )
+    }
+
+    /// Narrow the interval to suffixes which continue with next bytes.
+    /// Complexity O(|next| * log(range.count()))
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abracadabra");
+    /// let range = sa.range("a");
+    /// let range = sa.extend(range, "br");
+    /// let range = sa.extend(range, "a");
+    /// assert_eq!(range, sa.range("abra"));
+    /// ```
+    /// The range must be obtained from the same suffix array.
+    pub fn extend<P: AsRef<[u8]> + ?Sized>(&self, range: SaRange, next: &P) -> SaRange {
+        let next = next.as_ref();
+        let pattern_len = range.pattern_len + next.len();
+        if pattern_len == 0 {
+            return SaRange {
+                start: 0,
+                end: 0,
+                pattern_len,
+            };
+        }
+        // every suffix starts with the empty pattern
+        let range = if range.pattern_len == 0 {
+            SaRange {
+                start: 0,
+                end: self.sa.len(),
+                pattern_len: 0,
+            }
+        } else {
+            range
+        };
+        if range.is_empty() || next.is_empty() {
+            return SaRange {
+                pattern_len,
+                ..range
+            };
+        }
+        let (start, end) = refine_range(
+            self.text(),
+            &self.sa[range.as_range()],
+            range.pattern_len,
+            next,
+        );
+        SaRange {
+            start: range.start + start,
+            end: range.start + end,
+            pattern_len,
+        }
+    }
+
+    /// Return positions of all suffixes in the interval
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert_eq!(sa.positions(sa.range("ab")), &[2, 0]);
+    /// ```
+    #[inline]
+    pub fn positions(&self, range: SaRange) -> &[T] {
+        &self.sa[range.as_range()]
+    }
+
     /// Find substr. Complexity O(|word|)
     /// ```
     /// use suff_collections::array::*;
@@ -497,9 +945,12 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     /// assert_eq!(find, Some(1));
     /// ```
     #[inline]
-    pub fn find_big(&self, lcp: &LCP<T>, find: &str) -> Option<T> {
-        let idx = self.find_pos_big(lcp, find)?;
-        Some(self.sa[idx])
+    pub fn find_big<P: AsRef<[u8]> + ?Sized>(&self, lcp: &LCP<T>, find: &P) -> Option<T> {
+        let (start, end) = self.find_pos_big(lcp, find);
+        if start >= end {
+            return None;
+        }
+        Some(self.sa[start])
     }
 
     /// Find all substr. Complexity O(|word|)
@@ -511,61 +962,158 @@ impl<'sa, T: SuffixIndices<T>> SuffixArray<'sa, T> {
     /// assert_eq!(find, &[1]);
     /// ```
     #[inline]
-    pub fn find_all_big(&self, lcp: &LCP<T>, find: &str) -> &[T] {
-        match self.find_pos_big(lcp, find) {
-            None => &[],
-            Some(start) => {
-                let end = start
-                    + lcp
-                        .iter()
-                        .skip(start + 1)
-                        .take_while(|&&lcp| find.len() <= lcp.to_usize())
-                        .count();
-
-                &self.sa[start..=end]
-            }
-        }
+    pub fn find_all_big<P: AsRef<[u8]> + ?Sized>(&self, lcp: &LCP<T>, find: &P) -> &[T] {
+        let (start, end) = self.find_pos_big(lcp, find);
+        &self.sa[start..end]
     }
 
-    // O(|find| * log(|word|))
-    fn find_pos(&self, find: &str) -> (usize, usize) {
-        if find.is_empty() {
-            return (0, 0);
-        }
-        let (word, find) = (self.word.as_bytes(), find.as_bytes());
-        let start = binary_search(&self.sa, |&idx| &word[idx.to_usize()..] < find);
-
-        // skip all matches
-        let end = start
-            + binary_search(&self.sa[start..], |&idx| {
-                idx.to_usize() + find.len() < word.len()
-                    && &word[idx.to_usize()..idx.to_usize() + find.len()] == find
-            });
-
-        (start, end)
-    }
-    // O(|word|)
-    fn find_pos_big(&self, lcp: &LCP<T>, find: &str) -> Option<usize> {
-        if find.is_empty() {
+    /// Longest substr which occurs at least twice. It is None if there is no repeat.
+    /// Complexity O(n)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("banana");
+    /// let lrs = sa.longest_repeated_substring().unwrap();
+    /// assert_eq!(lrs.as_bytes(), b"ana");
+    /// assert_eq!(lrs.positions(), &[3, 1]);
+    /// assert!(SuffixArray::<usize>::new("word").longest_repeated_substring().is_none());
+    /// ```
+    pub fn longest_repeated_substring(&self) -> Option<Repeat<'_, T>> {
+        let (text, sa) = (self.text(), &self.sa);
+        // the sentinel is not compared, so repeats do not contain it
+        let lcp = lcp_kasai(text, sa);
+        let (i, len) = lcp.iter().enumerate().fold((0, 0), |(i, len), (j, x)| {
+            if x.to_usize() > len {
+                (j, x.to_usize())
+            } else {
+                (i, len)
+            }
+        });
+        if len == 0 {
             return None;
         }
-        let (word, find) = (self.word.as_bytes(), find.as_bytes());
-        // entry of the first character (byte) is searched for by means of binary search
-        let start = binary_search(&self.sa, |&idx| word[idx.to_usize()] < find[0]);
+        let rb = i + lcp[i + 1..]
+            .iter()
+            .take_while(|x| x.to_usize() >= len)
+            .count();
+        let from = sa[i].to_usize();
+        Some(Repeat::new(
+            &text[from..from + len],
+            Cow::from(&sa[i - 1..=rb]),
+        ))
+    }
 
-        let mut total_eq = 0;
-        for (&idx, i) in self.sa.iter().skip(start).zip((start + 1..).into_iter()) {
-            total_eq = count_eq(&word[idx.to_usize()..], &find, total_eq);
+    /// Iterator over maximal repeats with length >= min_len in post-order of lcp-intervals.
+    /// Complexity O(n + number of entries)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abcxabcyabc");
+    /// let repeats = sa
+    ///     .maximal_repeats(1)
+    ///     .map(|x| (x.as_bytes(), x.count()))
+    ///     .collect::<Vec<_>>();
+    /// // "bc" and "c" are always preceded by the same symbols
+    /// assert_eq!(repeats, vec![(&b"abc"[..], 3)]);
+    /// ```
+    pub fn maximal_repeats(&self, min_len: usize) -> impl Iterator<Item = Repeat<'_, T>> + '_ {
+        let (text, sa) = (self.text(), &self.sa);
+        let lcp = lcp_kasai(text, sa);
+        let breaks = LeftBreaks::new(text, sa);
+        let min_len = max(min_len, 1);
+        LcpIntervals::new(Cow::from(lcp.owned()))
+            .filter(move |(x, _)| x.lcp() >= min_len && breaks.is_left_maximal(x.lb(), x.rb()))
+            .map(move |(x, _)| {
+                let from = sa[x.lb()].to_usize();
+                Repeat::new(&text[from..from + x.lcp()], Cow::from(&sa[x.as_range()]))
+            })
+    }
 
-            if total_eq == find.len() {
-                return Some(i - 1);
+    /// Iterator over near-supermaximal repeats with length >= min_len in post-order
+    /// of lcp-intervals. The flag is true if the repeat is supermaximal: it is not
+    /// a prefix of a longer repeat and its entries are preceded by pairwise different
+    /// symbols (left-diverse). Complexity O(n + number of entries)
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("xabcyabcwabcyz");
+    /// let repeats = sa
+    ///     .supermaximal_repeats(1)
+    ///     .map(|(x, is_supermaximal)| (x.as_bytes(), x.count(), is_supermaximal))
+    ///     .collect::<Vec<_>>();
+    /// // "abcy" is not inside other repeats, "abc" at 5 is not inside "abcy"
+    /// assert_eq!(repeats, vec![(&b"abcy"[..], 2, true), (&b"abc"[..], 3, false)]);
+    /// ```
+    pub fn supermaximal_repeats(
+        &self,
+        min_len: usize,
+    ) -> impl Iterator<Item = (Repeat<'_, T>, bool)> + '_ {
+        let (text, sa) = (self.text(), &self.sa);
+        let lcp = lcp_kasai(text, sa);
+        let neighbours = LeftNeighbours::new(text, sa);
+        let min_len = max(min_len, 1);
+        LcpIntervals::new(Cow::from(lcp.owned())).filter_map(move |(x, children)| {
+            if x.lcp() < min_len {
+                return None;
             }
-
-            if i < lcp.len() && total_eq > lcp[i].to_usize() {
+            let is_unique = |i: usize| neighbours.is_left_unique(i, x.lb(), x.rb());
+            let is_supermaximal = children.is_empty() && x.as_range().all(is_unique);
+            // an entry is not inside other maximal repeats if it is a leaf
+            // of the interval with the unique preceding symbol
+            let mut from = x.lb();
+            let mut leaves = children
+                .iter()
+                .map(|child| (child.lb(), child.rb() + 1))
+                .chain(iter::once((x.rb() + 1, x.rb() + 1)))
+                .flat_map(|(lb, next)| {
+                    let leaves = from..lb;
+                    from = next;
+                    leaves
+                });
+            if !is_supermaximal && !leaves.any(is_unique) {
                 return None;
             }
-        }
-        None
+            let from = sa[x.lb()].to_usize();
+            let repeat = Repeat::new(&text[from..from + x.lcp()], Cow::from(&sa[x.as_range()]));
+            Some((repeat, is_supermaximal))
+        })
+    }
+
+    // word without sentinel. The sentinel is the smallest symbol, so
+    // suffixes of the text are sorted in the same order as suffixes of the word
+    #[inline]
+    pub(crate) fn text(&self) -> &[u8] {
+        &self.word[..self.word.len().saturating_sub(1)]
+    }
+
+    // O(|find| * log(|word|))
+    fn find_pos<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> (usize, usize) {
+        find_range(self.text(), &self.sa, find.as_ref())
+    }
+    // O(|word|)
+    fn find_pos_big<P: AsRef<[u8]> + ?Sized>(&self, lcp: &LCP<T>, find: &P) -> (usize, usize) {
+        find_range_big(self.text(), &self.sa, lcp.inner(), find.as_ref())
+    }
+}
+
+impl<T: SuffixIndices<T>> SuffixArray<'_, T> {
+    /// Construct suffix array from suffix tree without panic. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, error::*, tree::*};
+    ///
+    /// let sa = SuffixArray::<u8>::try_from_tree(SuffixTree::new("word"));
+    /// assert!(sa.is_ok());
+    /// let sa = SuffixArray::<u8>::try_from_tree(SuffixTree::new("wo\0rd"));
+    /// assert_eq!(sa.unwrap_err(), SuffixError::InvalidSentinel { pos: 2 });
+    /// ```
+    /// Same as `SuffixArray::from`, but return error if word.len() >= T::MAX
+    /// or the word of the tree contains '\0' not at the end.
+    /// `TryFrom` is not implemented because it is already provided by `From`.
+    pub fn try_from_tree(tree: SuffixTree) -> Result<Self, SuffixError> {
+        let word = tree.word().as_bytes();
+        check_index_size::<T>(word.len())?;
+        check_sentinel(word)?;
+        Ok(Self::from(tree))
     }
 }
 
@@ -581,23 +1129,7 @@ impl<T: SuffixIndices<T>> From<SuffixTree<'_>> for SuffixArray<'_, T> {
     /// let sa = SuffixArray::<usize>::from(st);
     /// ```
     fn from(tree: SuffixTree) -> Self {
-        let word = if tree.word().as_bytes().last() == Some(&0) {
-            Cow::from(tree.word().to_owned())
-        } else {
-            Cow::from(
-                str::from_utf8(
-                    &tree
-                        .word()
-                        .as_bytes()
-                        .iter()
-                        .chain(&[0])
-                        .copied()
-                        .collect::<Vec<_>>(),
-                )
-                .unwrap()
-                .to_owned(),
-            )
-        };
+        let word = Cow::from(canonic_word(tree.word().as_bytes()).into_owned());
 
         let mut sa = Vec::with_capacity(word.len());
         let mut stack = Vec::with_capacity(word.len());
@@ -639,7 +1171,274 @@ impl<T: SuffixIndices<T>> From<SuffixTree<'_>> for SuffixArray<'_, T> {
     }
 }
 
-fn binary_search<T>(x: &[T], cmp: impl Fn(&T) -> bool) -> usize {
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Builder {
+    Rec,
+    Stack,
+    #[cfg(feature = "rayon")]
+    Par,
+}
+
+// Build suffix array of s_idx. s_idx.last() must be the unique
+// smallest symbol and max(s_idx) < dict_size.
+pub(crate) fn sort_suffixes<S, T, BitLayout>(
+    s_idx: &[S],
+    dict_size: usize,
+    builder: Builder,
+) -> Vec<T>
+where
+    S: ToUsize + Ord + Copy + Sync,
+    T: SuffixIndices<T>,
+    BitLayout: Layout,
+{
+    #[cfg(feature = "rayon")]
+    if let Builder::Par = builder {
+        return crate::par_build::suffix_array(s_idx, dict_size);
+    }
+    let mut offset_dict = vec![(T::zero(), T::zero()); max(s_idx.len(), dict_size)];
+    let mut tmp_end_s = vec![T::zero(); offset_dict.len()];
+    let mut sa = vec![T::zero(); s_idx.len()];
+    let mut sa_init = BitLayout::ret_bytes(s_idx.len());
+    // safe because
+    //      offset_dict.len() > max(s_idx) && offset_dict.len() >= s_idx.len()
+    //      tmp_end_s.len() == offset_dict.len()
+    //      sa.len() == s_idx.len()
+    //      sa_init covers sa.len() elements
+    //      s_idx.last() == 0
+    debug_assert!(
+        offset_dict.len() > s_idx.iter().max().unwrap().to_usize()
+            && offset_dict.len() >= s_idx.len()
+            && tmp_end_s.len() == offset_dict.len()
+            && sa.len() == s_idx.len()
+            && s_idx.last().unwrap().to_usize() == 0
+    );
+    unsafe {
+        match builder {
+            Builder::Rec => build_suffix_array::suffix_array(
+                s_idx,
+                &mut offset_dict,
+                &mut tmp_end_s,
+                &mut sa,
+                &mut BitLayout::to_bits(&mut sa_init),
+            ),
+            Builder::Stack => build_suffix_array::suffix_array_stack(
+                s_idx,
+                &mut offset_dict,
+                &mut tmp_end_s,
+                &mut sa,
+                &mut BitLayout::to_bits(&mut sa_init),
+            ),
+            #[cfg(feature = "rayon")]
+            Builder::Par => unreachable!(),
+        }
+    }
+    sa
+}
+
+// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+// Kasai et al. Complexity O(n)
+// word is the text without the sentinel, so the sentinel is smaller than every symbol
+pub(crate) fn lcp_kasai<S: Eq, T: SuffixIndices<T>>(word: &[S], sa: &[T]) -> LCP<T> {
+    lcp_kasai_with_rank(word, sa, &rank(sa))
+}
+
+// rank[sa[i]] = i
+pub(crate) fn rank<T: SuffixIndices<T>>(sa: &[T]) -> Vec<T> {
+    let mut rank = vec![T::zero(); sa.len()];
+    // safe max(sa) < rank.len()
+    sa.iter().enumerate().for_each(|(i, &x)| unsafe {
+        *rank.get_unchecked_mut(x.to_usize()) = T::try_from(i).ok().unwrap()
+    });
+    rank
+}
+
+// rank must be a permutation of 0..sa.len() and word.len() + 1 == sa.len()
+pub(crate) fn lcp_kasai_with_rank<S: Eq, T: SuffixIndices<T>>(
+    word: &[S],
+    sa: &[T],
+    rank: &[T],
+) -> LCP<T> {
+    let mut lcp = LCP::<T>::new(vec![T::zero(); sa.len()]);
+
+    let mut pref_len = T::zero();
+    for &x in rank {
+        let x = x.to_usize() + 1;
+        if x == sa.len() {
+            pref_len = T::zero();
+            continue;
+        }
+
+        // safe max(rank) < sa.len() && x < sa.len() by previous check
+        // safe l <= word.len() && r <= word.len() because word is sa.len() - 1 symbols
+        let l = unsafe { *sa.get_unchecked(x - 1) };
+        let r = unsafe { *sa.get_unchecked(x) };
+        pref_len = unsafe {
+            count_eq(
+                word.get_unchecked(l.to_usize()..),
+                word.get_unchecked(r.to_usize()..),
+                pref_len,
+            )
+        };
+
+        // safe x < sa.len() by previous check && lcp.len() == sa.len()
+        unsafe {
+            *lcp.idx_mut(x) = pref_len;
+        }
+        if pref_len > T::zero() {
+            pref_len -= T::one();
+        }
+    }
+    lcp
+}
+
+// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+// Kärkkäinen et al. Complexity O(n)
+// word is the text without the sentinel, so the sentinel is smaller than every symbol
+pub(crate) fn lcp_phi<S: Eq, T: SuffixIndices<T>>(word: &[S], sa: &[T]) -> LCP<T> {
+    // phi[sa[i]] = sa[i - 1] and phi[sa[0]] = T::max() because sa.len() < T::max()
+    let mut plcp = vec![<T as Max>::max(); sa.len()];
+    sa.windows(2).for_each(|x| plcp[x[1].to_usize()] = x[0]);
+
+    // plcp[i] = lcp of suffix i and its predecessor in sa, plcp[i + 1] >= plcp[i] - 1
+    let mut pref_len = T::zero();
+    for (i, x) in plcp.iter_mut().enumerate() {
+        if *x == <T as Max>::max() {
+            pref_len = T::zero();
+        } else {
+            pref_len = count_eq(&word[i..], &word[x.to_usize()..], pref_len);
+        }
+        *x = pref_len;
+        if pref_len > T::zero() {
+            pref_len -= T::one();
+        }
+    }
+    LCP::new(sa.iter().map(|&x| plcp[x.to_usize()]).collect())
+}
+
+// O(|find| * log(|text|))
+// Bounds of all suffixes of text in sa which start with find
+pub(crate) fn find_range<S: Ord, T: SuffixIndices<T>>(
+    text: &[S],
+    sa: &[T],
+    find: &[S],
+) -> (usize, usize) {
+    if find.is_empty() {
+        return (0, 0);
+    }
+    refine_range(text, sa, 0, find)
+}
+
+// O(|text|)
+// Bounds of all suffixes of text in sa which start with find, lcp of sa is used
+pub(crate) fn find_range_big<T: SuffixIndices<T>>(
+    text: &[u8],
+    sa: &[T],
+    lcp: &[T],
+    find: &[u8],
+) -> (usize, usize) {
+    if find.is_empty() {
+        return (0, 0);
+    }
+    // entry of the first character (byte) is searched for by means of binary search
+    let start = binary_search(sa, |&idx| text[idx.to_usize()..] < find[..1]);
+
+    let mut total_eq = 0;
+    for (&idx, i) in sa.iter().skip(start).zip(start + 1..) {
+        total_eq = count_eq(&text[idx.to_usize()..], find, total_eq);
+
+        if total_eq == find.len() {
+            // skip all matches
+            let end = i + lcp
+                .iter()
+                .skip(i)
+                .take_while(|&lcp| find.len() <= lcp.to_usize())
+                .count();
+            return (i - 1, end);
+        }
+
+        if i < lcp.len() && total_eq > lcp[i].to_usize() {
+            break;
+        }
+    }
+    (0, 0)
+}
+
+// O(|find| * log(|sa|))
+// Bounds of all suffixes in sa which start with find after skip symbols.
+// All suffixes in sa must be not shorter than skip
+pub(crate) fn refine_range<S: Ord, T: SuffixIndices<T>>(
+    text: &[S],
+    sa: &[T],
+    skip: usize,
+    find: &[S],
+) -> (usize, usize) {
+    let start = binary_search(sa, |&idx| &text[idx.to_usize() + skip..] < find);
+
+    // skip all matches
+    let end = start
+        + binary_search(&sa[start..], |&idx| {
+            text[idx.to_usize() + skip..].starts_with(find)
+        });
+
+    (start, end)
+}
+
+// O(n)
+// Check that sa is the suffix array of word with the sentinel at the end
+#[cfg(feature = "serde")]
+pub(crate) fn check_suffix_array<T: SuffixIndices<T>>(
+    word: &[u8],
+    sa: &[T],
+) -> Result<(), &'static str> {
+    if sa.len() != word.len() {
+        return Err("length of suffix array is not equal to length of word");
+    }
+    if word.last().is_some_and(|&x| x != 0) {
+        return Err("word does not end with the sentinel");
+    }
+    let mut rank = vec![usize::MAX; sa.len()];
+    for (i, &idx) in sa.iter().enumerate() {
+        match rank.get_mut(idx.to_usize()) {
+            Some(x) if *x == usize::MAX => *x = i,
+            _ => return Err("suffix array is not a permutation"),
+        }
+    }
+    // suffix is (first symbol, rest suffix) and the sentinel is less than any symbol
+    let key = |idx: usize| {
+        if idx + 1 == word.len() {
+            (0, 0)
+        } else {
+            (word[idx] as usize + 1, rank[idx + 1])
+        }
+    };
+    if sa
+        .windows(2)
+        .any(|x| key(x[0].to_usize()) >= key(x[1].to_usize()))
+    {
+        return Err("suffixes are not sorted");
+    }
+    Ok(())
+}
+
+// all positions of the word with the sentinel must fit in T
+pub(crate) fn check_index_size<T: SuffixIndices<T>>(len: usize) -> Result<(), SuffixError> {
+    let max = <T as Max>::max().to_usize();
+    if len < max {
+        Ok(())
+    } else {
+        Err(SuffixError::IndexTooSmall { len, max })
+    }
+}
+
+// '\0' is allowed only at the end of the word
+pub(crate) fn check_sentinel(word: &[u8]) -> Result<(), SuffixError> {
+    match word.iter().position(|&x| x == 0) {
+        Some(pos) if pos + 1 != word.len() => Err(SuffixError::InvalidSentinel { pos }),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn binary_search<T>(x: &[T], cmp: impl Fn(&T) -> bool) -> usize {
     let mut start = 0;
     let mut cnt = x.len();
     while cnt > 0 {
@@ -655,7 +1454,7 @@ fn binary_search<T>(x: &[T], cmp: impl Fn(&T) -> bool) -> usize {
     start
 }
 
-fn count_eq<T: Eq, P: SuffixIndices<P>>(cmp1: &[T], cmp2: &[T], mut acc: P) -> P {
+pub(crate) fn count_eq<T: Eq, P: SuffixIndices<P>>(cmp1: &[T], cmp2: &[T], mut acc: P) -> P {
     while acc.to_usize() < cmp1.len()
         && acc.to_usize() < cmp2.len()
         && cmp1[acc.to_usize()] == cmp2[acc.to_usize()]
@@ -764,6 +1563,8 @@ pub(crate) mod build_suffix_array {
         + Clone
         + Copy
         + Default
+        + Send
+        + Sync
     {
     }
 
@@ -0,0 +1,344 @@
+//! Versioned binary format of the suffix array, lcp (optional) and word,
+//! and `SuffixArrayRef` which searches directly over the stored bytes (for example, a memory-mapped file)
+//!
+//! All numbers are little-endian. The buffer starts with the 32 bytes header
+//!
+//! | offset | size | field                                                     |
+//! |--------|------|-----------------------------------------------------------|
+//! | 0      | 8    | magic `b"SUFFCOLS"`                                       |
+//! | 8      | 2    | version of the format, now 1                              |
+//! | 10     | 1    | index width in bytes (1, 2, 4 or 8)                       |
+//! | 11     | 1    | flags, bit 0 is set if lcp is stored                      |
+//! | 12     | 4    | reserved, zeros                                           |
+//! | 16     | 8    | n, length of the word with the sentinel                   |
+//! | 24     | 8    | FNV-1a 64 checksum of all bytes except this field         |
+//!
+//! Then follow the sections, each one is padded with zeros to a multiple of 8 bytes
+//! * the word with the sentinel, n bytes
+//! * the suffix array, n indices
+//! * lcp, n indices (if the flag is set)
+//!
+//! So the indices are aligned if the buffer is aligned to 8 bytes.
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{array::*, binary::*};
+//!
+//! let sa = SuffixArray::<u8>::new("Some word and other word");
+//!
+//! // serialize with lcp
+//! // let bytes: Vec<u8> = sa.to_binary(None);
+//! let bytes: Vec<u8> = sa.to_binary(Some(&sa.lcp()));
+//!
+//! // borrow the suffix array from the buffer without copying. Complexity O(n)
+//! // the buffer must be aligned for the index type, a memory-mapped file is aligned to the page
+//! let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+//! assert_eq!(view.find_all("word"), sa.find_all("word"));
+//! assert_eq!(view.find_big("word"), sa.find_big(&sa.lcp(), "word"));
+//! ```
+
+use alloc::vec::Vec;
+use core::{
+    convert::{TryFrom, TryInto},
+    mem,
+    option::Option,
+};
+
+use crate::{
+    array::{build_suffix_array::SuffixIndices, find_range, find_range_big, SuffixArray},
+    error::FormatError,
+    lcp::LCP,
+};
+
+pub const MAGIC: [u8; 8] = *b"SUFFCOLS";
+pub const VERSION: u16 = 1;
+pub const HEADER_LEN: usize = 32;
+
+const FLAG_LCP: u8 = 1;
+const CHECKSUM: core::ops::Range<usize> = 24..32;
+
+/// Suffix array, lcp and word borrowed from the buffer of the binary format
+#[derive(Debug, Clone, Copy)]
+pub struct SuffixArrayRef<'sa, T: SuffixIndices<T>> {
+    word: &'sa [u8],
+    sa: &'sa [T],
+    lcp: Option<&'sa [T]>,
+}
+
+impl<'sa, T: SuffixIndices<T>> SuffixArrayRef<'sa, T> {
+    /// Check the header and the checksum and borrow the sections of buffer. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, binary::*, error::*};
+    ///
+    /// let bytes = SuffixArray::<u8>::new("word").to_binary(None);
+    /// // u8 indices are always aligned
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.find("or"), Some(1));
+    ///
+    /// let err = SuffixArrayRef::<u8>::from_bytes(&bytes[..40]).unwrap_err();
+    /// assert_eq!(err, FormatError::Truncated { len: 40, expected: 48 });
+    /// ```
+    /// The buffer must be aligned for T, otherwise `FormatError::Misaligned` is returned.
+    /// The order of suffixes is not verified, the checksum protects from damaged data.
+    pub fn from_bytes(buf: &'sa [u8]) -> Result<Self, FormatError> {
+        if cfg!(target_endian = "big") {
+            return Err(FormatError::BigEndian);
+        }
+        if buf.len() < HEADER_LEN {
+            return Err(FormatError::Truncated {
+                len: buf.len(),
+                expected: HEADER_LEN,
+            });
+        }
+        if buf[..8] != MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+        let version = u16::from_le_bytes(buf[8..10].try_into().unwrap());
+        if version != VERSION {
+            return Err(FormatError::UnsupportedVersion { version });
+        }
+        let width = buf[10] as usize;
+        if width != mem::size_of::<T>() {
+            return Err(FormatError::IndexWidth {
+                expected: mem::size_of::<T>(),
+                found: width,
+            });
+        }
+        let has_lcp = buf[11] & FLAG_LCP != 0;
+
+        let len = u64::from_le_bytes(buf[16..24].try_into().unwrap());
+        let (word_len, sa_len, expected) = usize::try_from(len)
+            .ok()
+            .and_then(|n| section_lens(n, width, has_lcp))
+            .ok_or(FormatError::TooLarge { len })?;
+        if buf.len() < expected {
+            return Err(FormatError::Truncated {
+                len: buf.len(),
+                expected,
+            });
+        }
+        let buf = &buf[..expected];
+        let checksum = u64::from_le_bytes(buf[CHECKSUM].try_into().unwrap());
+        if checksum != fnv1a(buf) {
+            return Err(FormatError::ChecksumMismatch);
+        }
+
+        let n = len as usize;
+        let word = &buf[HEADER_LEN..HEADER_LEN + n];
+        let sa_from = HEADER_LEN + word_len;
+        let sa = cast::<T>(&buf[sa_from..sa_from + n * width])?;
+        let lcp = if has_lcp {
+            let lcp_from = sa_from + sa_len;
+            Some(cast::<T>(&buf[lcp_from..lcp_from + n * width])?)
+        } else {
+            None
+        };
+
+        // all searches index the word by values of the suffix array
+        if word.last().is_some_and(|&x| x != 0) || sa.iter().any(|&idx| idx.to_usize() >= n) {
+            return Err(FormatError::InvalidData);
+        }
+        Ok(Self { word, sa, lcp })
+    }
+
+    /// Return ref on suffix array
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let bytes = SuffixArray::<u8>::new("word").to_binary(None);
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.suffix_array(), &[4, 3, 1, 2, 0]);
+    /// ```
+    #[inline]
+    pub fn suffix_array(&self) -> &'sa [T] {
+        self.sa
+    }
+
+    /// Return ref on lcp if it is stored
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let sa = SuffixArray::<u8>::new("word");
+    /// let bytes = sa.to_binary(Some(&sa.lcp()));
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.lcp(), Some(sa.lcp().inner()));
+    /// ```
+    #[inline]
+    pub fn lcp(&self) -> Option<&'sa [T]> {
+        self.lcp
+    }
+
+    /// Return ref on word with the sentinel
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let bytes = SuffixArray::<u8>::new("word").to_binary(None);
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.word_bytes(), b"word\0");
+    /// ```
+    #[inline]
+    pub fn word_bytes(&self) -> &'sa [u8] {
+        self.word
+    }
+
+    /// Find substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let bytes = SuffixArray::<u8>::new("word").to_binary(None);
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.find("or"), Some(1));
+    /// ```
+    #[inline]
+    pub fn find<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Option<T> {
+        self.find_all(find).first().copied()
+    }
+
+    /// Find all substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let bytes = SuffixArray::<u8>::new("abab").to_binary(None);
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.find_all("ab"), &[2, 0]);
+    /// ```
+    #[inline]
+    pub fn find_all<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> &'sa [T] {
+        let (start, end) = find_range(self.text(), self.sa, find.as_ref());
+        &self.sa[start..end]
+    }
+
+    /// Find substr using stored lcp. Complexity O(|word|)
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let sa = SuffixArray::<u8>::new("word");
+    /// let bytes = sa.to_binary(Some(&sa.lcp()));
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.find_big("or"), Some(1));
+    /// ```
+    /// If lcp is not stored then `find` is used.
+    #[inline]
+    pub fn find_big<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Option<T> {
+        self.find_all_big(find).first().copied()
+    }
+
+    /// Find all substr using stored lcp. Complexity O(|word|)
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let sa = SuffixArray::<u8>::new("abab");
+    /// let bytes = sa.to_binary(Some(&sa.lcp()));
+    /// let view = SuffixArrayRef::<u8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.find_all_big("ab"), &[2, 0]);
+    /// ```
+    /// If lcp is not stored then `find_all` is used.
+    #[inline]
+    pub fn find_all_big<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> &'sa [T] {
+        match self.lcp {
+            Some(lcp) => {
+                let (start, end) = find_range_big(self.text(), self.sa, lcp, find.as_ref());
+                &self.sa[start..end]
+            }
+            None => self.find_all(find),
+        }
+    }
+
+    // word without sentinel
+    #[inline]
+    fn text(&self) -> &'sa [u8] {
+        &self.word[..self.word.len().saturating_sub(1)]
+    }
+}
+
+// word must end with the sentinel
+pub(crate) fn to_binary<T: SuffixIndices<T>>(
+    word: &[u8],
+    sa: &[T],
+    lcp: Option<&LCP<T>>,
+) -> Vec<u8> {
+    let width = mem::size_of::<T>();
+    let (word_len, sa_len, total) = section_lens(word.len(), width, lcp.is_some()).unwrap();
+
+    let mut buf = Vec::with_capacity(total);
+    buf.extend_from_slice(&MAGIC);
+    buf.extend_from_slice(&VERSION.to_le_bytes());
+    buf.push(width as u8);
+    buf.push(if lcp.is_some() { FLAG_LCP } else { 0 });
+    buf.extend_from_slice(&[0; 4]);
+    buf.extend_from_slice(&(word.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&[0; 8]);
+
+    buf.extend_from_slice(word);
+    buf.resize(HEADER_LEN + word_len, 0);
+    let push_indices = |buf: &mut Vec<u8>, x: &[T]| {
+        let from = buf.len();
+        x.iter().for_each(|&idx| {
+            buf.extend_from_slice(&(idx.to_usize() as u64).to_le_bytes()[..width])
+        });
+        buf.resize(from + sa_len, 0);
+    };
+    push_indices(&mut buf, sa);
+    if let Some(lcp) = lcp {
+        push_indices(&mut buf, lcp.inner());
+    }
+
+    let checksum = fnv1a(&buf);
+    buf[CHECKSUM].copy_from_slice(&checksum.to_le_bytes());
+    buf
+}
+
+impl<T: SuffixIndices<T>> SuffixArray<'_, T> {
+    /// Serialize the suffix array, the word and lcp (optional) to the binary format,
+    /// see module `binary`. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, binary::*};
+    ///
+    /// let sa = SuffixArray::<u8>::new("word");
+    /// let bytes: Vec<u8> = sa.to_binary(Some(&sa.lcp()));
+    /// assert_eq!(&bytes[..8], &MAGIC);
+    /// assert_eq!(bytes.len(), HEADER_LEN + 3 * 8);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if lcp.len() != suffix array len.
+    pub fn to_binary(&self, lcp: Option<&LCP<T>>) -> Vec<u8> {
+        if let Some(lcp) = lcp {
+            assert_eq!(lcp.len(), self.suffix_array().len());
+        }
+        to_binary(self.word_bytes(), self.suffix_array(), lcp)
+    }
+}
+
+// lengths of the padded word and index sections and of the whole buffer
+fn section_lens(n: usize, width: usize, has_lcp: bool) -> Option<(usize, usize, usize)> {
+    let pad = |x: usize| x.checked_add(7).map(|x| x & !7);
+    let word_len = pad(n)?;
+    let sa_len = pad(n.checked_mul(width)?)?;
+    let total = HEADER_LEN
+        .checked_add(word_len)?
+        .checked_add(sa_len.checked_mul(1 + has_lcp as usize)?)?;
+    Some((word_len, sa_len, total))
+}
+
+// FNV-1a 64 of all bytes except the checksum field
+fn fnv1a(buf: &[u8]) -> u64 {
+    buf[..CHECKSUM.start]
+        .iter()
+        .chain(&buf[CHECKSUM.end..])
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &x| {
+            (hash ^ x as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+fn cast<T: SuffixIndices<T>>(bytes: &[u8]) -> Result<&[T], FormatError> {
+    // safe because T is one of the primitive unsigned integers, any bit pattern is valid,
+    // and the target is little-endian as the format
+    let (prefix, x, suffix) = unsafe { bytes.align_to::<T>() };
+    if !prefix.is_empty() || !suffix.is_empty() {
+        return Err(FormatError::Misaligned);
+    }
+    Ok(x)
+}
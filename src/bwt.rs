@@ -0,0 +1,94 @@
+//! Implementation of the [Burrows–Wheeler transform](https://en.wikipedia.org/wiki/Burrows%E2%80%93Wheeler_transform)
+//! bwt\[i\] = word\[sa\[i\] - 1\] and the sentinel stands at the primary index (sa\[primary\] == 0)
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{array::*, bwt::*};
+//!
+//! let sa = SuffixArray::<usize>::new("banana");
+//!
+//! // transformed bytes with the sentinel and the primary index
+//! let (bwt, primary): (Vec<u8>, usize) = sa.bwt();
+//! assert_eq!(bwt, b"annb\0aa");
+//!
+//! // restore the word with the sentinel. Complexity O(n)
+//! let word: Vec<u8> = inverse_bwt(&bwt, primary);
+//! assert_eq!(word, sa.word_bytes());
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::array::build_suffix_array::SuffixIndices;
+
+/// Restore the word (with the sentinel at the end) from bwt and primary index.
+/// Complexity O(n)
+/// ```
+/// use suff_collections::bwt::*;
+///
+/// let word = inverse_bwt(b"annb\0aa", 4);
+/// assert_eq!(word, b"banana\0");
+/// ```
+/// Byte at primary index is the sentinel, it is less than any other byte
+/// including 0 inside the word.
+///
+/// # Panics
+///
+/// This function will panic if bwt is not empty and primary >= bwt.len().
+pub fn inverse_bwt(bwt: &[u8], primary: usize) -> Vec<u8> {
+    if bwt.is_empty() {
+        return vec![];
+    }
+    assert!(primary < bwt.len());
+
+    // the sentinel has key 0, byte x has key x + 1
+    let key = |i: usize| {
+        if i == primary {
+            0
+        } else {
+            bwt[i] as usize + 1
+        }
+    };
+    let mut buckets = [0; 257];
+    (0..bwt.len()).for_each(|i| buckets[key(i)] += 1);
+    let mut sum = 0;
+    for x in buckets.iter_mut() {
+        let cnt = *x;
+        *x = sum;
+        sum += cnt;
+    }
+    // lf[i] is the row of the suffix which is one symbol longer than suffix of the row i
+    let lf = (0..bwt.len())
+        .map(|i| {
+            let k = key(i);
+            buckets[k] += 1;
+            buckets[k] - 1
+        })
+        .collect::<Vec<_>>();
+
+    // the row 0 is the sentinel suffix
+    let mut word = vec![0; bwt.len()];
+    let mut row = 0;
+    for x in word[..bwt.len() - 1].iter_mut().rev() {
+        *x = bwt[row];
+        row = lf[row];
+    }
+    word
+}
+
+// word must end with the sentinel
+pub(crate) fn bwt<T: SuffixIndices<T>>(word: &[u8], sa: &[T]) -> (Vec<u8>, usize) {
+    let mut primary = 0;
+    let bwt = sa
+        .iter()
+        .enumerate()
+        .map(|(i, &idx)| match idx.to_usize() {
+            0 => {
+                primary = i;
+                word[word.len() - 1]
+            }
+            idx => word[idx - 1],
+        })
+        .collect();
+    (bwt, primary)
+}
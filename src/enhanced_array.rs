@@ -0,0 +1,312 @@
+//! Implementation of the [enhanced suffix array](https://www.sciencedirect.com/science/article/pii/S1570866703000650)
+//! (suffix array, lcp and child table). It simulates the top-down traversal of the suffix tree
+//! over flat arrays. Construction of the child table is performed in linear time.
+//! Children of an interval are visited one by one, so search takes O(|find| * σ)
+//! where σ is the number of different bytes of the word
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{array::*, enhanced_array::*, lcp::*};
+//!
+//! let sa = SuffixArray::<usize>::new("Some word and other word");
+//!
+//! // construct lcp and child table. O(n)
+//! let esa = EnhancedSuffixArray::new(sa);
+//!
+//! // finds all the entry position of the line 'word'
+//! // O(|find| * σ)
+//! let res_all: &[usize] = esa.find_all("word");
+//! assert_eq!(res_all, &[20, 5]);
+//!
+//! // top-down traversal of lcp-intervals (nodes of the suffix tree)
+//! let root: LcpInterval = esa.root().unwrap();
+//! for child in esa.children(root) {
+//!     let suffixes: &[usize] = &esa.suffix_array().suffix_array()[child.as_range()];
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use core::{cmp::min, iter, option::Option};
+
+use crate::{
+    array::{
+        build_suffix_array::{Max, SuffixIndices},
+        SuffixArray,
+    },
+    lcp::*,
+};
+
+#[derive(Debug, Clone)]
+pub struct EnhancedSuffixArray<'sa, T: SuffixIndices<T>> {
+    sa: SuffixArray<'sa, T>,
+    lcp: LCP<T>,
+    // child[i] is next l-index of i if it exists, otherwise first l-index of the interval after i (down).
+    // child[i - 1] is first l-index of the interval before i (up) if lcp[i - 1] > lcp[i]
+    child: Vec<T>,
+}
+
+impl<'sa, T: SuffixIndices<T>> EnhancedSuffixArray<'sa, T> {
+    /// Construct lcp and child table of suffix array. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("word"));
+    /// ```
+    pub fn new(sa: SuffixArray<'sa, T>) -> Self {
+        let lcp = sa.lcp();
+        let undef = <T as Max>::max();
+        let mut child = vec![undef; lcp.len()];
+        let mut esa = Self {
+            sa,
+            lcp,
+            child: vec![],
+        };
+        let n = esa.lcp.len();
+
+        // up and down
+        let mut stack = vec![0];
+        for i in 1..=n {
+            let mut last = None;
+            while esa.l(i) < esa.l(*stack.last().unwrap()) {
+                let last_idx = stack.pop().unwrap();
+                last = Some(last_idx);
+                let top = *stack.last().unwrap();
+                if esa.l(i) <= esa.l(top) && esa.l(top) != esa.l(last_idx) {
+                    child[top] = T::try_from(last_idx).ok().unwrap();
+                }
+            }
+            if let Some(last_idx) = last {
+                child[i - 1] = T::try_from(last_idx).ok().unwrap();
+            }
+            stack.push(i);
+        }
+
+        // next l-index, down of the same index is not needed if it exists
+        let mut stack = vec![0];
+        for i in 1..n {
+            while esa.l(i) < esa.l(*stack.last().unwrap()) {
+                stack.pop();
+            }
+            if esa.l(i) == esa.l(*stack.last().unwrap()) {
+                let last_idx = stack.pop().unwrap();
+                child[last_idx] = T::try_from(i).ok().unwrap();
+            }
+            stack.push(i);
+        }
+
+        esa.child = child;
+        esa
+    }
+
+    /// Return ref on suffix array
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("word"));
+    /// let sa: &SuffixArray<usize> = esa.suffix_array();
+    /// ```
+    #[inline]
+    pub fn suffix_array(&self) -> &SuffixArray<'sa, T> {
+        &self.sa
+    }
+
+    /// Return ref on lcp, the same as `SuffixArray::lcp`
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// let lcp = sa.lcp();
+    /// let esa = EnhancedSuffixArray::new(sa);
+    /// assert_eq!(esa.lcp().inner(), lcp.inner());
+    /// ```
+    #[inline]
+    pub fn lcp(&self) -> &LCP<T> {
+        &self.lcp
+    }
+
+    /// Return ref on child table
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("word"));
+    /// assert_eq!(esa.child_table().len(), "word\0".len());
+    /// ```
+    #[inline]
+    pub fn child_table(&self) -> &[T] {
+        &self.child
+    }
+
+    /// Return interval of all suffixes. It is None if the suffix array is empty
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("word"));
+    /// let root = esa.root().unwrap();
+    /// assert_eq!((root.lcp(), root.lb(), root.rb()), (0, 0, 4));
+    /// ```
+    #[inline]
+    pub fn root(&self) -> Option<LcpInterval> {
+        match self.lcp.len() {
+            0 => None,
+            n => Some(self.interval(0, n - 1)),
+        }
+    }
+
+    /// Return child intervals in the suffix array order. Complexity O(number of children)
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abab"));
+    /// let children = esa
+    ///     .children(esa.root().unwrap())
+    ///     .map(|x| (x.lcp(), x.lb(), x.rb()))
+    ///     .collect::<Vec<_>>();
+    /// // "", "ab", "b"
+    /// assert_eq!(children, vec![(0, 0, 0), (2, 1, 2), (1, 3, 4)]);
+    /// ```
+    /// The interval must be obtained from the same enhanced suffix array.
+    pub fn children(&self, interval: LcpInterval) -> impl Iterator<Item = LcpInterval> + '_ {
+        let (lb, rb) = (interval.lb(), interval.rb());
+        let mut next = if interval.is_leaf() {
+            None
+        } else {
+            Some((lb, Some(self.first_l_index(lb, rb))))
+        };
+        iter::from_fn(move || {
+            let (from, l_idx) = next?;
+            match l_idx {
+                Some(l_idx) => {
+                    next = Some((l_idx, self.next_l_index(l_idx)));
+                    Some(self.interval(from, l_idx - 1))
+                }
+                None => {
+                    next = None;
+                    Some(self.interval(from, rb))
+                }
+            }
+        })
+    }
+
+    /// Iterator over lcp-intervals in post-order, see `LCP::bottom_up`. Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("banana"));
+    /// let root = esa.bottom_up().last().unwrap().0;
+    /// assert_eq!(Some(root), esa.root());
+    /// ```
+    #[inline]
+    pub fn bottom_up(&self) -> LcpIntervals<'_, T> {
+        self.lcp.bottom_up()
+    }
+
+    /// Find substr. Complexity O(|find| * σ)
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("word"));
+    /// assert_eq!(esa.find("or"), Some(1));
+    /// ```
+    #[inline]
+    pub fn find<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Option<T> {
+        self.find_all(find).first().copied()
+    }
+
+    /// Find all substr. Complexity O(|find| * σ)
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abab"));
+    /// assert_eq!(esa.find_all("ab"), &[2, 0]);
+    /// ```
+    /// The child with the next byte is found by the linear scan of children,
+    /// so every byte of find costs up to σ steps.
+    pub fn find_all<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> &[T] {
+        let (find, text, sa) = (find.as_ref(), self.sa.text(), self.sa.suffix_array());
+        let mut node = match self.root() {
+            Some(root) if !find.is_empty() => root,
+            _ => return &[],
+        };
+        let mut depth = 0;
+        loop {
+            // all suffixes of the interval have the same prefix of length node.lcp()
+            let end = min(node.lcp(), find.len());
+            let from = sa[node.lb()].to_usize();
+            if text[from + depth..from + end] != find[depth..end] {
+                return &[];
+            }
+            if find.len() <= node.lcp() {
+                return &sa[node.as_range()];
+            }
+            if node.is_leaf() {
+                return &[];
+            }
+            depth = node.lcp();
+            node = match self
+                .children(node)
+                .find(|x| text.get(sa[x.lb()].to_usize() + depth) == Some(&find[depth]))
+            {
+                Some(x) => x,
+                None => return &[],
+            };
+        }
+    }
+
+    /// Number of entries of substr. Complexity O(|find| * σ)
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abracadabra"));
+    /// assert_eq!(esa.count("abra"), 2);
+    /// ```
+    #[inline]
+    pub fn count<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> usize {
+        self.find_all(find).len()
+    }
+
+    // lcp[i] + 1 with the bounds lower than any lcp
+    #[inline]
+    fn l(&self, i: usize) -> usize {
+        if i == 0 || i >= self.lcp.len() {
+            0
+        } else {
+            self.lcp[i].to_usize() + 1
+        }
+    }
+
+    // lb < rb
+    fn first_l_index(&self, lb: usize, rb: usize) -> usize {
+        if self.l(rb) > self.l(rb + 1) {
+            // up of rb + 1
+            let up = self.child[rb].to_usize();
+            if lb < up && up <= rb {
+                return up;
+            }
+        }
+        // down of lb
+        self.child[lb].to_usize()
+    }
+
+    fn next_l_index(&self, i: usize) -> Option<usize> {
+        let next = self.child[i];
+        if next == <T as Max>::max() {
+            return None;
+        }
+        let next = next.to_usize();
+        if next > i && self.l(next) == self.l(i) {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    fn interval(&self, lb: usize, rb: usize) -> LcpInterval {
+        if lb == rb {
+            let len = self.sa.text().len() - self.sa.suffix_array()[lb].to_usize();
+            return LcpInterval::new(len, lb, rb);
+        }
+        let l_idx = self.first_l_index(lb, rb);
+        LcpInterval::new(self.lcp[l_idx].to_usize(), lb, rb)
+    }
+}
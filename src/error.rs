@@ -0,0 +1,81 @@
+//! Errors of the fallible construction and of the binary format loading
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuffixError {
+    /// Index type can't hold the length of the word
+    IndexTooSmall { len: usize, max: usize },
+    /// Word is empty
+    EmptyInput,
+    /// '\0' inside the word at `pos`, it is reserved for the sentinel
+    InvalidSentinel { pos: usize },
+}
+
+impl fmt::Display for SuffixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuffixError::IndexTooSmall { len, max } => write!(
+                f,
+                "index type is too small: word length {} must be less than {}",
+                len, max
+            ),
+            SuffixError::EmptyInput => write!(f, "word is empty"),
+            SuffixError::InvalidSentinel { pos } => {
+                write!(f, "'\\0' at position {} is reserved for the sentinel", pos)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatError {
+    /// Buffer does not start with the magic bytes
+    BadMagic,
+    /// Version of the format is not supported
+    UnsupportedVersion { version: u16 },
+    /// Width of the stored indices is not equal to the width of the index type
+    IndexWidth { expected: usize, found: usize },
+    /// Buffer is shorter than the header requires
+    Truncated { len: usize, expected: usize },
+    /// Stored length of the word overflows the size of the buffer
+    TooLarge { len: u64 },
+    /// Checksum of the buffer does not match the header
+    ChecksumMismatch,
+    /// Buffer is not aligned for the index type
+    Misaligned,
+    /// Stored data is not a suffix array of the stored word
+    InvalidData,
+    /// Zero-copy loading requires a little-endian target
+    BigEndian,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::BadMagic => write!(f, "buffer does not start with the magic bytes"),
+            FormatError::UnsupportedVersion { version } => {
+                write!(f, "unsupported format version {}", version)
+            }
+            FormatError::IndexWidth { expected, found } => write!(
+                f,
+                "index width mismatch: expected {} bytes, found {}",
+                expected, found
+            ),
+            FormatError::Truncated { len, expected } => write!(
+                f,
+                "buffer is truncated: length {} is less than {}",
+                len, expected
+            ),
+            FormatError::TooLarge { len } => {
+                write!(f, "stored word length {} is too large", len)
+            }
+            FormatError::ChecksumMismatch => write!(f, "checksum mismatch"),
+            FormatError::Misaligned => write!(f, "buffer is not aligned for the index type"),
+            FormatError::InvalidData => write!(f, "stored data is not a valid suffix array"),
+            FormatError::BigEndian => {
+                write!(f, "zero-copy loading requires a little-endian target")
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Implementation of the [FM-index](https://en.wikipedia.org/wiki/FM-index)
+//! over bwt of the suffix array. bwt is kept in the wavelet matrix, so counting
+//! is performed in O(|find| * log(σ)) and locating uses the sampled suffix array
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{array::*, fm_index::*};
+//!
+//! let sa = SuffixArray::<usize>::new("Some word and other word");
+//!
+//! // keep every 4-th suffix array value
+//! let fm = FmIndex::new(&sa, 4);
+//! // the suffix array (and the word) is no longer needed
+//! drop(sa);
+//!
+//! // number of entries of 'word'. O(|find| * log(σ))
+//! let cnt: usize = fm.count("word");
+//!
+//! // all the entry position of 'word'. O((|find| + occ * sample_rate) * log(σ))
+//! let res_all: Vec<usize> = fm.locate("word");
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::array::{build_suffix_array::SuffixIndices, SuffixArray};
+
+/// FM-index over the word with σ different bytes.
+/// Memory per byte of the word is about (1 + size_of::<T>() / 64) * ⌈log2(σ + 1)⌉ / 8 bytes
+/// for bwt with rank, 1 / 8 byte for marks of the sampled rows and size_of::<T>() / sample_rate
+/// bytes for the sampled suffix array. For example, an english text (σ < 128) with u32 indices
+/// and sample_rate = 32 takes about 1.2 bytes per byte of the text
+#[derive(Debug, Clone)]
+pub struct FmIndex<T: SuffixIndices<T>> {
+    primary: usize,
+    // code[x] is index of byte x in the alphabet of bwt, the sentinel has code c.len()
+    code: [u16; 256],
+    // symbols[code] is byte of code
+    symbols: Vec<u8>,
+    // c[code] number of symbols in bwt which are less than symbol (the sentinel included)
+    c: Vec<usize>,
+    // codes of bwt
+    bwt: WaveletMatrix<T>,
+    sample_rate: usize,
+    // bit i is set if sa[i] % sample_rate == 0
+    sampled: RankBits<T>,
+    // sa[i] of sampled rows in order of rows
+    samples: Vec<T>,
+}
+
+impl<T: SuffixIndices<T>> FmIndex<T> {
+    const ABSENT: u16 = u16::MAX;
+
+    /// Construct FM-index from bwt of the suffix array. Complexity O(n * log(σ))
+    /// ```
+    /// use suff_collections::{array::*, fm_index::*};
+    ///
+    /// let sa = SuffixArray::<u32>::new("word");
+    /// // let fm = FmIndex::new(&sa, 1);
+    /// // let fm = FmIndex::new(&sa, 16);
+    /// let fm = FmIndex::new(&sa, 32);
+    /// ```
+    /// Every `sample_rate`-th position of the word is kept, so `locate`
+    /// needs at most `sample_rate` steps per entry.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if sample_rate == 0.
+    pub fn new(sa: &SuffixArray<T>, sample_rate: usize) -> Self {
+        assert!(sample_rate > 0);
+        let (bwt, primary) = sa.bwt();
+        let sa = sa.suffix_array();
+
+        let mut cnt = [0; 256];
+        bwt.iter().for_each(|&x| cnt[x as usize] += 1);
+        if !bwt.is_empty() {
+            // the sentinel is not the 0 symbol
+            cnt[0] -= 1;
+        }
+        let mut code = [Self::ABSENT; 256];
+        let (mut symbols, mut c) = (Vec::new(), Vec::new());
+        let mut sum = if bwt.is_empty() { 0 } else { 1 };
+        for (x, &n) in cnt.iter().enumerate().filter(|(_, &n)| n > 0) {
+            code[x] = c.len() as u16;
+            symbols.push(x as u8);
+            c.push(sum);
+            sum += n;
+        }
+        let codes = bwt
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                if i == primary {
+                    c.len()
+                } else {
+                    code[x as usize] as usize
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut sampled = vec![0u64; sa.len() / 64 + 1];
+        let mut samples = Vec::with_capacity(sa.len() / sample_rate + 1);
+        for (i, &idx) in sa.iter().enumerate() {
+            if idx.to_usize() % sample_rate == 0 {
+                sampled[i / 64] |= 1 << (i % 64);
+                samples.push(idx);
+            }
+        }
+
+        Self {
+            primary,
+            code,
+            symbols,
+            bwt: WaveletMatrix::new(&codes, c.len()),
+            c,
+            sample_rate,
+            sampled: RankBits::new(sampled),
+            samples,
+        }
+    }
+
+    /// Number of entries of substr. Complexity O(|find| * log(σ))
+    /// ```
+    /// use suff_collections::{array::*, fm_index::*};
+    ///
+    /// let fm = FmIndex::new(&SuffixArray::<usize>::new("abracadabra"), 4);
+    /// assert_eq!(fm.count("abra"), 2);
+    /// assert_eq!(fm.count("abc"), 0);
+    /// ```
+    pub fn count<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> usize {
+        let (start, end) = self.range(find.as_ref());
+        end - start
+    }
+
+    /// Find all substr. Return positions in suffix array order.
+    /// Complexity O((|find| + occ * sample_rate) * log(σ))
+    /// ```
+    /// use suff_collections::{array::*, fm_index::*};
+    ///
+    /// let fm = FmIndex::new(&SuffixArray::<usize>::new("abracadabra"), 4);
+    /// assert_eq!(fm.locate("abra"), vec![7, 0]);
+    /// ```
+    pub fn locate<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Vec<T> {
+        let (start, end) = self.range(find.as_ref());
+        (start..end).map(|row| self.suffix_at(row)).collect()
+    }
+
+    /// Restore bwt and primary index, see `SuffixArray::bwt`. Complexity O(n * log(σ))
+    /// ```
+    /// use suff_collections::{array::*, fm_index::*};
+    ///
+    /// let fm = FmIndex::new(&SuffixArray::<usize>::new("banana"), 4);
+    /// assert_eq!(fm.bwt(), (b"annb\0aa".to_vec(), 4));
+    /// ```
+    pub fn bwt(&self) -> (Vec<u8>, usize) {
+        let bwt = (0..self.bwt.len())
+            .map(|i| self.symbols.get(self.bwt.get(i)).copied().unwrap_or(0))
+            .collect();
+        (bwt, self.primary)
+    }
+
+    /// Return sample rate of suffix array
+    /// ```
+    /// use suff_collections::{array::*, fm_index::*};
+    ///
+    /// let fm = FmIndex::new(&SuffixArray::<usize>::new("banana"), 4);
+    /// assert_eq!(fm.sample_rate(), 4);
+    /// ```
+    #[inline]
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    // backward search of rows which start with find. O(|find| * log(σ))
+    fn range(&self, find: &[u8]) -> (usize, usize) {
+        if find.is_empty() {
+            return (0, 0);
+        }
+        let (mut start, mut end) = (0, self.bwt.len());
+        for &x in find.iter().rev() {
+            let code = self.code[x as usize];
+            if code == Self::ABSENT {
+                return (0, 0);
+            }
+            let code = code as usize;
+            start = self.c[code] + self.bwt.rank(code, start);
+            end = self.c[code] + self.bwt.rank(code, end);
+            if start >= end {
+                return (0, 0);
+            }
+        }
+        (start, end)
+    }
+
+    // go to the previous suffix until the sampled one. O(sample_rate * log(σ))
+    fn suffix_at(&self, mut row: usize) -> T {
+        let mut steps = 0;
+        while !self.sampled.get(row) {
+            // the primary row is always sampled because sa[primary] == 0
+            let code = self.bwt.get(row);
+            row = self.c[code] + self.bwt.rank(code, row);
+            steps += 1;
+        }
+        self.samples[self.sampled.rank(row)] + T::try_from(steps).ok().unwrap()
+    }
+}
+
+// Bit vector with the number of set bits before every WORDS words. Rank is O(WORDS)
+#[derive(Debug, Clone)]
+struct RankBits<T: SuffixIndices<T>> {
+    bits: Vec<u64>,
+    // ranks[i] is the number of set bits in bits[..i * WORDS]
+    ranks: Vec<T>,
+}
+
+impl<T: SuffixIndices<T>> RankBits<T> {
+    const WORDS: usize = 8;
+
+    // the last word must not be full, so rank(i) is defined for i <= 64 * (bits.len() - 1)
+    fn new(bits: Vec<u64>) -> Self {
+        let mut ranks = Vec::with_capacity(bits.len() / Self::WORDS + 1);
+        let mut rank = 0;
+        for words in bits.chunks(Self::WORDS) {
+            ranks.push(T::try_from(rank).ok().unwrap());
+            rank += words.iter().map(|x| x.count_ones() as usize).sum::<usize>();
+        }
+        Self { bits, ranks }
+    }
+
+    #[inline]
+    fn get(&self, i: usize) -> bool {
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    // number of set bits in [..i]
+    #[inline]
+    fn rank(&self, i: usize) -> usize {
+        let (word, block) = (i / 64, i / 64 / Self::WORDS);
+        self.ranks[block].to_usize()
+            + self.bits[block * Self::WORDS..word]
+                .iter()
+                .map(|x| x.count_ones() as usize)
+                .sum::<usize>()
+            + (self.bits[word] & ((1 << (i % 64)) - 1)).count_ones() as usize
+    }
+}
+
+// Wavelet matrix (Claude, Navarro, Ordóñez) of codes < 2^levels.len().
+// levels[l] keeps bit levels.len() - 1 - l of the codes, which are stable sorted
+// by the higher bits, zeros[l] is the number of unset bits of the level
+#[derive(Debug, Clone)]
+struct WaveletMatrix<T: SuffixIndices<T>> {
+    len: usize,
+    levels: Vec<RankBits<T>>,
+    zeros: Vec<usize>,
+}
+
+impl<T: SuffixIndices<T>> WaveletMatrix<T> {
+    // O(n * log(max_code))
+    fn new(codes: &[usize], max_code: usize) -> Self {
+        let depth = (usize::BITS - max_code.leading_zeros()) as usize;
+        let (mut levels, mut zeros) = (Vec::with_capacity(depth), Vec::with_capacity(depth));
+        let mut cur = codes.to_vec();
+        for shift in (0..depth).rev() {
+            let mut bits = vec![0u64; cur.len() / 64 + 1];
+            let (mut lo, mut hi) = (Vec::with_capacity(cur.len()), Vec::new());
+            for (i, &x) in cur.iter().enumerate() {
+                if x >> shift & 1 == 1 {
+                    bits[i / 64] |= 1 << (i % 64);
+                    hi.push(x);
+                } else {
+                    lo.push(x);
+                }
+            }
+            zeros.push(lo.len());
+            lo.extend(hi);
+            cur = lo;
+            levels.push(RankBits::new(bits));
+        }
+        Self {
+            len: codes.len(),
+            levels,
+            zeros,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    // code at i. O(log(max_code))
+    fn get(&self, mut i: usize) -> usize {
+        let mut code = 0;
+        for (level, &zeros) in self.levels.iter().zip(&self.zeros) {
+            let bit = level.get(i);
+            i = if bit {
+                zeros + level.rank(i)
+            } else {
+                i - level.rank(i)
+            };
+            code = code << 1 | bit as usize;
+        }
+        code
+    }
+
+    // number of code in [..i]. O(log(max_code))
+    fn rank(&self, code: usize, mut i: usize) -> usize {
+        // start of the codes with the same higher bits
+        let mut start = 0;
+        for (shift, (level, &zeros)) in self.levels.iter().zip(&self.zeros).rev().enumerate().rev()
+        {
+            if code >> shift & 1 == 1 {
+                start = zeros + level.rank(start);
+                i = zeros + level.rank(i);
+            } else {
+                start -= level.rank(start);
+                i -= level.rank(i);
+            }
+        }
+        i - start
+    }
+}
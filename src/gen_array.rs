@@ -0,0 +1,497 @@
+//! Implementation of the generalized suffix array over a collection of documents.
+//! Documents are concatenated with distinct separators and the suffix array
+//! of the concatenation is built with [SA-IS](https://www.researchgate.net/profile/Daricks_Wai_Hong_Chan/publication/221577802_Linear_Suffix_Array_Construction_by_Almost_Pure_Induced-Sorting/links/00b495318a21ba484f000000/Linear-Suffix-Array-Construction-by-Almost-Pure-Induced-Sorting.pdf?origin=publication_detail)
+//! in linear time
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{gen_array::*, lcp::*};
+//!
+//! let docs: &[&str] = &["Some word", "Other words", "Nothing"];
+//! let find: &str = "word";
+//!
+//! // construct generalized suffix array
+//! // let gsa = GeneralizedSuffixArray::<u8>::new(docs);
+//! // let gsa = GeneralizedSuffixArray::<u16>::new(docs);
+//! // let gsa = GeneralizedSuffixArray::<u32>::new(docs);
+//! let gsa = GeneralizedSuffixArray::<usize>::new(docs);
+//!
+//! // construct lcp
+//! // lcp[i] = max_pref(sa[i], sa[i - 1]) && lcp.len() == sa.len()
+//! let lcp: LCP<usize> = gsa.lcp();
+//!
+//! // finds all the entry (doc_id, offset) of the line 'find' in docs
+//! // O(|find| * log(n))
+//! let res_all: Vec<(usize, usize)> = gsa.find_all(find).collect();
+//!
+//! // finds all documents which contain the line 'find'. Each document is reported once
+//! let res_docs: Vec<usize> = gsa.find_docs(find);
+//! assert_eq!(res_docs, vec![0, 1]);
+//!
+//! // number of entries of the line 'find' in each document
+//! let res_counts: Vec<(usize, usize)> = gsa.doc_counts(find);
+//! assert_eq!(res_counts, vec![(0, 1), (1, 1)]);
+//!
+//! // longest substr which is contained in at least 2 documents with all its entries
+//! let (lcs, entries): (&[u8], Vec<(usize, usize)>) = gsa.longest_common_substring(2);
+//! assert_eq!(lcs, b" word");
+//!
+//! // longest common substr of two words with its positions in each of them
+//! let (lcs, in_a, in_b) = longest_common_substring("Some word", "Other words");
+//! assert_eq!((lcs, in_a, in_b), (&b" word"[..], vec![4], vec![5]));
+//!
+//! // maximal unique matches of two words as (pos_in_a, pos_in_b, len) with len >= 3
+//! let mums = maximal_unique_matches("Some word", "Other words", 3);
+//! assert_eq!(mums, vec![(4, 5, 5)]);
+//! ```
+
+use alloc::{collections::VecDeque, vec::Vec};
+use core::cmp::max;
+use core::slice::Iter;
+
+use crate::{array::*, lcp::*};
+use build_suffix_array::{Max, SuffixIndices};
+
+#[derive(Debug, Clone)]
+pub struct GeneralizedSuffixArray<T: SuffixIndices<T>> {
+    // documents one after another, each one followed by separator slot
+    text: Vec<u8>,
+    // starts[i] is start of document i, starts.last() == text.len()
+    starts: Vec<T>,
+    sa: Vec<T>,
+}
+
+impl<T: SuffixIndices<T>> GeneralizedSuffixArray<T> {
+    /// Construct generalized suffix array recursive. Complexity O(n + |docs|)
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// // let gsa = GeneralizedSuffixArray::<u8>::new(&["ab", "b"]);
+    /// // let gsa = GeneralizedSuffixArray::<u16>::new(&["ab", "b"]);
+    /// // let gsa = GeneralizedSuffixArray::<u32>::new(&["ab", "b"]);
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(vec![vec![0xff, 0x00], vec![0x00]]);
+    /// ```
+    /// Each document is followed by its own separator which is smaller than any byte
+    /// and different from the separators of other documents, so documents can contain any bytes.
+    /// Positions in the suffix array are positions in the concatenation of documents
+    /// and separators, use `doc_pos` to translate them.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if total length of documents and separators > T::MAX.
+    pub fn new<I, D>(docs: I) -> Self
+    where
+        I: IntoIterator<Item = D>,
+        D: AsRef<[u8]>,
+    {
+        let mut text = Vec::new();
+        let mut starts = Vec::new();
+        for doc in docs {
+            starts.push(text.len());
+            text.extend_from_slice(doc.as_ref());
+            text.push(0);
+        }
+        starts.push(text.len());
+        assert!(text.len() < <T as Max>::max().to_usize());
+        let cnt_docs = starts.len() - 1;
+        assert!(cnt_docs <= (u32::MAX - 256) as usize);
+
+        let mut s_idx = Vec::with_capacity(text.len());
+        for (i, doc) in starts.windows(2).enumerate() {
+            // bytes are shifted above all separators. The last separator
+            // is 0 because SA-IS needs a unique smallest symbol at the end
+            s_idx.extend(
+                text[doc[0]..doc[1] - 1]
+                    .iter()
+                    .map(|&x| x as u32 + cnt_docs as u32),
+            );
+            s_idx.push((cnt_docs - 1 - i) as u32);
+        }
+        let sa = if s_idx.is_empty() {
+            vec![]
+        } else {
+            sort_suffixes::<_, T, ByteSliceMut>(&s_idx, 256 + cnt_docs, Builder::Rec)
+        };
+
+        Self {
+            text,
+            starts: starts
+                .into_iter()
+                .map(|x| T::try_from(x).ok().unwrap())
+                .collect(),
+            sa,
+        }
+    }
+
+    /// Return iterator on suffix array
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// GeneralizedSuffixArray::<usize>::new(&["ab", "b"]).iter().for_each(|&idx| println!("idx: {}", idx));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.sa.iter()
+    }
+
+    /// Return ref on suffix array
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// // separators go first
+    /// assert_eq!(gsa.suffix_array(), &[4, 2, 0, 3, 1]);
+    /// ```
+    #[inline]
+    pub fn suffix_array(&self) -> &Vec<T> {
+        &self.sa
+    }
+
+    /// Return ref on concatenation of documents. Separators are stored as 0
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// assert_eq!(gsa.text(), b"ab\0b\0");
+    /// ```
+    #[inline]
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Return number of documents
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// assert_eq!(gsa.doc_count(), 2);
+    /// ```
+    #[inline]
+    pub fn doc_count(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    /// Return ref on document
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// assert_eq!(gsa.doc(1), b"b");
+    /// ```
+    #[inline]
+    pub fn doc(&self, doc_id: usize) -> &[u8] {
+        &self.text[self.starts[doc_id].to_usize()..self.starts[doc_id + 1].to_usize() - 1]
+    }
+
+    /// Return start position of document in the concatenation
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// assert_eq!(gsa.doc_start(1), 3);
+    /// ```
+    #[inline]
+    pub fn doc_start(&self, doc_id: usize) -> usize {
+        self.starts[doc_id].to_usize()
+    }
+
+    /// Translate position in the concatenation to (doc_id, offset).
+    /// Complexity O(log(|docs|))
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// assert_eq!(gsa.doc_pos(3), (1, 0));
+    /// // separator of document 0
+    /// assert_eq!(gsa.doc_pos(2), (0, 2));
+    /// ```
+    #[inline]
+    pub fn doc_pos(&self, pos: T) -> (usize, usize) {
+        let pos = pos.to_usize();
+        let doc_id = binary_search(&self.starts, |&x| x.to_usize() <= pos) - 1;
+        (doc_id, pos - self.starts[doc_id].to_usize())
+    }
+
+    /// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+    /// Common prefix never crosses the end of document.
+    /// Construct LCP. Complexity O(n * log(|docs|))
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let lcp = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]).lcp();
+    /// assert_eq!(lcp.inner(), &[0, 0, 0, 0, 1]);
+    /// ```
+    pub fn lcp(&self) -> LCP<T> {
+        let mut lcp = vec![T::zero(); self.sa.len()];
+        let mut rank = vec![T::zero(); self.sa.len()];
+        self.sa
+            .iter()
+            .enumerate()
+            .for_each(|(i, &x)| rank[x.to_usize()] = T::try_from(i).ok().unwrap());
+
+        let mut pref_len = T::zero();
+        for (i, &x) in rank.iter().enumerate() {
+            if x == T::zero() {
+                pref_len = T::zero();
+                continue;
+            }
+            let prev = self.sa[x.to_usize() - 1].to_usize();
+            pref_len = count_eq(self.suffix(i), self.suffix(prev), pref_len);
+            lcp[x.to_usize()] = pref_len;
+            if pref_len > T::zero() {
+                pref_len -= T::one();
+            }
+        }
+        LCP::new(lcp)
+    }
+
+    /// Find substr. Return (doc_id, offset). Complexity O(|find| * log(n))
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// assert_eq!(gsa.find("ab"), Some((0, 0)));
+    /// assert_eq!(gsa.find("ba"), None);
+    /// ```
+    #[inline]
+    pub fn find<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Option<(usize, usize)> {
+        let (start, end) = self.find_pos(find.as_ref());
+        if start >= end {
+            return None;
+        }
+        Some(self.doc_pos(self.sa[start]))
+    }
+
+    /// Find all substr. Return iterator on (doc_id, offset) in suffix array order.
+    /// Complexity O(|find| * log(n) + occ * log(|docs|))
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["ab", "b"]);
+    /// let find: Vec<(usize, usize)> = gsa.find_all("b").collect();
+    /// assert_eq!(find, vec![(1, 0), (0, 1)]);
+    /// ```
+    #[inline]
+    pub fn find_all<'s, P: AsRef<[u8]> + ?Sized>(
+        &'s self,
+        find: &P,
+    ) -> impl Iterator<Item = (usize, usize)> + 's {
+        let (start, end) = self.find_pos(find.as_ref());
+        self.sa[start..end].iter().map(move |&x| self.doc_pos(x))
+    }
+
+    /// Find all documents which contain substr. Each document is reported once.
+    /// Return sorted doc ids. Complexity O(|find| * log(n) + occ * log(occ))
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["abab", "c", "b"]);
+    /// assert_eq!(gsa.find_docs("b"), vec![0, 2]);
+    /// ```
+    pub fn find_docs<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Vec<usize> {
+        let mut docs = self
+            .find_all(find)
+            .map(|(doc_id, _)| doc_id)
+            .collect::<Vec<_>>();
+        docs.sort_unstable();
+        docs.dedup();
+        docs
+    }
+
+    /// Number of entries of substr in each document which contains it.
+    /// Return (doc_id, count) sorted by doc id. Complexity O(|find| * log(n) + occ * log(occ))
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["abab", "c", "b"]);
+    /// assert_eq!(gsa.doc_counts("b"), vec![(0, 2), (2, 1)]);
+    /// ```
+    pub fn doc_counts<P: AsRef<[u8]> + ?Sized>(&self, find: &P) -> Vec<(usize, usize)> {
+        let mut docs = self
+            .find_all(find)
+            .map(|(doc_id, _)| doc_id)
+            .collect::<Vec<_>>();
+        docs.sort_unstable();
+        let mut counts: Vec<(usize, usize)> = Vec::new();
+        for doc_id in docs {
+            match counts.last_mut() {
+                Some((last, cnt)) if *last == doc_id => *cnt += 1,
+                _ => counts.push((doc_id, 1)),
+            }
+        }
+        counts
+    }
+
+    /// Longest substr which is contained in at least k documents with all its entries
+    /// as (doc_id, offset) in suffix array order. The substr is empty if there is no such one.
+    /// Complexity O(n * log(|docs|) + occ * log(|docs|))
+    /// ```
+    /// use suff_collections::gen_array::*;
+    ///
+    /// let gsa = GeneralizedSuffixArray::<usize>::new(&["sandwich", "handwork", "wandering"]);
+    /// let (lcs, entries) = gsa.longest_common_substring(2);
+    /// assert_eq!(lcs, b"andw");
+    /// assert_eq!(entries, vec![(0, 1), (1, 1)]);
+    /// assert_eq!(gsa.longest_common_substring(3).0, b"and");
+    /// assert_eq!(gsa.longest_common_substring(4).0, b"");
+    /// ```
+    pub fn longest_common_substring(&self, k: usize) -> (&[u8], Vec<(usize, usize)>) {
+        let (mut best_len, mut best_pos) = (0, 0);
+        if k <= 1 {
+            // the longest document
+            for doc_id in 0..self.doc_count() {
+                if self.doc(doc_id).len() > best_len {
+                    best_len = self.doc(doc_id).len();
+                    best_pos = self.doc_start(doc_id);
+                }
+            }
+        } else if k <= self.doc_count() {
+            // separators go first and have no common prefix with others
+            let sa = &self.sa[self.doc_count()..];
+            let lcp = &self.lcp()[self.doc_count()..];
+            let docs = sa.iter().map(|&x| self.doc_pos(x).0).collect::<Vec<_>>();
+
+            // window sa[left..=right] with at least k documents, counts of its documents
+            // and indices of increasing minimums of lcp[left + 1..=right]
+            let mut counts = vec![0; self.doc_count()];
+            let (mut distinct, mut left) = (0, 0);
+            let mut mins = VecDeque::new();
+            for right in 0..sa.len() {
+                if counts[docs[right]] == 0 {
+                    distinct += 1;
+                }
+                counts[docs[right]] += 1;
+                if right > 0 {
+                    while mins.back().is_some_and(|&i| lcp[i] >= lcp[right]) {
+                        mins.pop_back();
+                    }
+                    mins.push_back(right);
+                }
+                // the shortest window which ends at right
+                while counts[docs[left]] > 1 || distinct > k {
+                    counts[docs[left]] -= 1;
+                    if counts[docs[left]] == 0 {
+                        distinct -= 1;
+                    }
+                    left += 1;
+                    while mins.front().is_some_and(|&i| i <= left) {
+                        mins.pop_front();
+                    }
+                }
+                if distinct >= k {
+                    let len = lcp[*mins.front().unwrap()].to_usize();
+                    if len > best_len {
+                        best_len = len;
+                        best_pos = sa[right].to_usize();
+                    }
+                }
+            }
+        }
+        let lcs = &self.text[best_pos..best_pos + best_len];
+        (lcs, self.find_all(lcs).collect())
+    }
+
+    // suffix of text which starts at pos and ends at the end of its document
+    #[inline]
+    fn suffix(&self, pos: usize) -> &[u8] {
+        let doc_id = binary_search(&self.starts, |&x| x.to_usize() <= pos) - 1;
+        &self.text[pos..self.starts[doc_id + 1].to_usize() - 1]
+    }
+
+    // O(|find| * log(n))
+    fn find_pos(&self, find: &[u8]) -> (usize, usize) {
+        if find.is_empty() {
+            return (0, 0);
+        }
+        // the separator is smaller than any byte, so suffix cut at the end of
+        // document compares with find like a slice
+        let start = binary_search(&self.sa, |&idx| self.suffix(idx.to_usize()) < find);
+
+        // skip all matches
+        let end = start
+            + binary_search(&self.sa[start..], |&idx| {
+                self.suffix(idx.to_usize()).starts_with(find)
+            });
+
+        (start, end)
+    }
+}
+
+/// Longest common substr of two words with all its entries in each of them.
+/// The substr is empty if there is no such one. Complexity O(n)
+/// ```
+/// use suff_collections::gen_array::*;
+///
+/// let (lcs, in_a, in_b) = longest_common_substring("abcxbcd", "zbcdbc");
+/// assert_eq!(lcs, b"bcd");
+/// assert_eq!((in_a, in_b), (vec![4], vec![1]));
+/// ```
+pub fn longest_common_substring<'a, A, B>(a: &'a A, b: &B) -> (&'a [u8], Vec<usize>, Vec<usize>)
+where
+    A: AsRef<[u8]> + ?Sized,
+    B: AsRef<[u8]> + ?Sized,
+{
+    let (a, b) = (a.as_ref(), b.as_ref());
+    let gsa = GeneralizedSuffixArray::<usize>::new([a, b]);
+    let (lcs, entries) = gsa.longest_common_substring(2);
+    let (mut in_a, mut in_b) = (Vec::new(), Vec::new());
+    for (doc_id, offset) in entries {
+        if doc_id == 0 {
+            in_a.push(offset);
+        } else {
+            in_b.push(offset);
+        }
+    }
+    in_a.sort_unstable();
+    in_b.sort_unstable();
+    let lcs = match in_a.first() {
+        Some(&pos) => &a[pos..pos + lcs.len()],
+        None => &a[..0],
+    };
+    (lcs, in_a, in_b)
+}
+
+/// Maximal unique matches: common substrs with length >= min_len which occur exactly once
+/// in each word and can not be extended to the left or to the right.
+/// Return (pos_in_a, pos_in_b, len) sorted by pos_in_a. Complexity O(n)
+/// ```
+/// use suff_collections::gen_array::*;
+///
+/// let mums = maximal_unique_matches("acgtaacc", "cgtaccaa", 2);
+/// // "cgta", "aa" and "acc"; "c" is not unique and "ta" is inside "cgta"
+/// assert_eq!(mums, vec![(1, 0, 4), (4, 6, 2), (5, 3, 3)]);
+/// ```
+pub fn maximal_unique_matches<A, B>(a: &A, b: &B, min_len: usize) -> Vec<(usize, usize, usize)>
+where
+    A: AsRef<[u8]> + ?Sized,
+    B: AsRef<[u8]> + ?Sized,
+{
+    let (a, b) = (a.as_ref(), b.as_ref());
+    let gsa = GeneralizedSuffixArray::<usize>::new([a, b]);
+    let lcp = gsa.lcp();
+    let sa = gsa.suffix_array();
+    let min_len = max(min_len, 1);
+
+    let mut mums = Vec::new();
+    // separators go first
+    for i in gsa.doc_count() + 1..sa.len() {
+        let len = lcp[i];
+        // the match is unique if the common prefix of neighbours is shorter
+        if len < min_len || lcp[i - 1] >= len || lcp.inner().get(i + 1).is_some_and(|&x| x >= len) {
+            continue;
+        }
+        let (x, y) = (gsa.doc_pos(sa[i - 1]), gsa.doc_pos(sa[i]));
+        let (pos_a, pos_b) = match (x, y) {
+            ((0, pos_a), (1, pos_b)) | ((1, pos_b), (0, pos_a)) => (pos_a, pos_b),
+            _ => continue,
+        };
+        // the common prefix is already right maximal
+        if pos_a == 0 || pos_b == 0 || a[pos_a - 1] != b[pos_b - 1] {
+            mums.push((pos_a, pos_b, len));
+        }
+    }
+    mums.sort_unstable();
+    mums
+}
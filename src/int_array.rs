@@ -0,0 +1,291 @@
+//! Implementation of the [suffix array](https://www.researchgate.net/profile/Daricks_Wai_Hong_Chan/publication/221577802_Linear_Suffix_Array_Construction_by_Almost_Pure_Induced-Sorting/links/00b495318a21ba484f000000/Linear-Suffix-Array-Construction-by-Almost-Pure-Induced-Sorting.pdf?origin=publication_detail)
+//! over integer alphabet (u8, u16, u32, u64, usize symbols)
+//! construction of which is performed in linear time
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{int_array::*, lcp::*};
+//!
+//! // token ids < 1000
+//! let word: &[u32] = &[17, 999, 17, 5, 0];
+//! let find: &[u32] = &[17];
+//!
+//! // construct suffix array
+//! // let sa = IntSuffixArray::<u32, usize>::new_stack(word, 1000);
+//! // let sa = IntSuffixArray::<u32, u8>::new(word, 1000);
+//! // let sa = IntSuffixArray::<u32, u16>::new(word, 1000);
+//! // let sa = IntSuffixArray::<u32, u32>::new(word, 1000);
+//! let sa = IntSuffixArray::<u32, usize>::new(word, 1000);
+//!
+//! // construct lcp
+//! // lcp[i] = max_pref(sa[i], sa[i - 1]) && lcp.len() == sa.len()
+//! let lcp: LCP<usize> = sa.lcp();
+//!
+//! // finds the entry position of the line 'find' in 'word'
+//! // O(|find| * log(|word|))
+//! let res: Option<usize> = sa.find(find);
+//!
+//! // finds all the entry position of the line 'find' in 'word'
+//! // O(|find| * log(|word|))
+//! let res_all: &[usize] = sa.find_all(find);
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::{IntoIter, Vec};
+use core::{iter, slice::Iter};
+
+use crate::{array::*, bit::*, lcp::*};
+use build_suffix_array::{Layout, Max, SuffixIndices};
+
+#[derive(Debug, Clone)]
+pub struct IntSuffixArray<'sa, S: SuffixIndices<S>, T: SuffixIndices<T>> {
+    word: Cow<'sa, [S]>,
+    sa: Vec<T>,
+}
+
+impl<'sa, S: SuffixIndices<S>, T: SuffixIndices<T>> IntoIterator for IntSuffixArray<'sa, S, T> {
+    type Item = T;
+    type IntoIter = IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sa.into_iter()
+    }
+}
+
+impl<'sa, S: SuffixIndices<S>, T: SuffixIndices<T>> IntoIterator
+    for &'sa IntSuffixArray<'sa, S, T>
+{
+    type Item = &'sa T;
+    type IntoIter = Iter<'sa, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sa.iter()
+    }
+}
+
+impl<'sa, S: SuffixIndices<S>, T: SuffixIndices<T>> IntSuffixArray<'sa, S, T> {
+    /// Construct suffix array recursive. Complexity O(n + alphabet_size)
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// // let sa = IntSuffixArray::<u16, u8>::new(&[300, 2, 300], 301);
+    /// // let sa = IntSuffixArray::<u16, u16>::new(&[300, 2, 300], 301);
+    /// // let sa = IntSuffixArray::<u16, u32>::new(&[300, 2, 300], 301);
+    /// let sa = IntSuffixArray::<u16, usize>::new(&[300, 2, 300], 301);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// Every symbol must be less than `alphabet_size`.
+    /// The word will be copied and the sentinel 0 added to the end.
+    /// Zero symbols inside the word are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX or alphabet_size > S::MAX + 1
+    /// or the word contains a symbol >= alphabet_size.
+    pub fn new(word: &'sa [S], alphabet_size: usize) -> Self {
+        Self::build::<ByteSliceMut>(word, alphabet_size, Builder::Rec)
+    }
+
+    /// Construct suffix array recursive. Complexity O(n + alphabet_size).
+    /// Uses less memory to build than `new` by using bitpcking.
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let sa = IntSuffixArray::<u16, usize>::new_compress(&[300, 2, 300], 301);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// Every symbol must be less than `alphabet_size`.
+    /// The word will be copied and the sentinel 0 added to the end.
+    /// Zero symbols inside the word are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX or alphabet_size > S::MAX + 1
+    /// or the word contains a symbol >= alphabet_size.
+    pub fn new_compress(word: &'sa [S], alphabet_size: usize) -> Self {
+        Self::build::<BitArrMut>(word, alphabet_size, Builder::Rec)
+    }
+
+    /// Construct suffix array not recursive. Complexity O(n + alphabet_size)
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let sa = IntSuffixArray::<u16, usize>::new_stack(&[300, 2, 300], 301);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// Every symbol must be less than `alphabet_size`.
+    /// The word will be copied and the sentinel 0 added to the end.
+    /// Zero symbols inside the word are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX or alphabet_size > S::MAX + 1
+    /// or the word contains a symbol >= alphabet_size.
+    pub fn new_stack(word: &'sa [S], alphabet_size: usize) -> Self {
+        Self::build::<ByteSliceMut>(word, alphabet_size, Builder::Stack)
+    }
+
+    /// Construct suffix array not recursive. Complexity O(n + alphabet_size).
+    /// Uses less memory to build than `new_stack` by using bitpcking.
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let sa = IntSuffixArray::<u16, usize>::new_stack_compress(&[300, 2, 300], 301);
+    /// assert_eq!(sa.suffix_array(), &[3, 1, 2, 0]);
+    /// ```
+    /// Every symbol must be less than `alphabet_size`.
+    /// The word will be copied and the sentinel 0 added to the end.
+    /// Zero symbols inside the word are allowed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if word.len() > T::MAX or alphabet_size > S::MAX + 1
+    /// or the word contains a symbol >= alphabet_size.
+    pub fn new_stack_compress(word: &'sa [S], alphabet_size: usize) -> Self {
+        Self::build::<BitArrMut>(word, alphabet_size, Builder::Stack)
+    }
+
+    fn build<BitLayout: Layout>(word: &'sa [S], alphabet_size: usize, builder: Builder) -> Self {
+        assert!(word.len() < <T as Max>::max().to_usize());
+        assert!(alphabet_size.saturating_sub(1) <= <S as Max>::max().to_usize());
+        // out of range symbol breaks bounds of the bucket dictionary
+        assert!(word.iter().all(|x| x.to_usize() < alphabet_size));
+        if word.is_empty() {
+            return Self {
+                word: Cow::from(&[][..]),
+                sa: vec![],
+            };
+        }
+        // the trailing 0 is a symbol of the word, so the sentinel is always added
+        let word = word
+            .iter()
+            .copied()
+            .chain(iter::once(S::zero()))
+            .collect::<Vec<_>>();
+        let text = &word[..word.len() - 1];
+        let sa = if !text.contains(&S::zero()) {
+            sort_suffixes::<_, T, BitLayout>(&word, alphabet_size, builder)
+        } else if alphabet_size <= <S as Max>::max().to_usize() {
+            // sentinel must be the unique smallest symbol, see SuffixArray::from_bytes
+            let s_idx = text
+                .iter()
+                .map(|&x| x + S::one())
+                .chain(iter::once(S::zero()))
+                .collect::<Vec<_>>();
+            sort_suffixes::<_, T, BitLayout>(&s_idx, alphabet_size + 1, builder)
+        } else {
+            // the shifted symbols do not fit into S
+            let s_idx = text
+                .iter()
+                .map(|&x| x.to_usize() + 1)
+                .chain(iter::once(0))
+                .collect::<Vec<_>>();
+            sort_suffixes::<_, T, BitLayout>(&s_idx, alphabet_size + 1, builder)
+        };
+
+        Self {
+            word: Cow::from(word),
+            sa,
+        }
+    }
+
+    /// Return iterator on suffix array
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// IntSuffixArray::<u32, usize>::new(&[3, 1, 2], 4).iter().for_each(|&idx| println!("idx: {}", idx));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.sa.iter()
+    }
+
+    /// Split suffix array
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let (word, sa) = IntSuffixArray::<u32, usize>::new(&[3, 1, 2], 4).split_owned();
+    /// ```
+    #[inline]
+    pub fn split_owned(self) -> (Cow<'sa, [S]>, Vec<T>) {
+        (self.word, self.sa)
+    }
+
+    /// Return ref on suffix array
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let sa = IntSuffixArray::<u32, usize>::new(&[3, 1, 2], 4);
+    /// let sa: &[usize] = sa.suffix_array();
+    /// ```
+    #[inline]
+    pub fn suffix_array(&self) -> &Vec<T> {
+        &self.sa
+    }
+
+    /// Return ref on word
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let sa = IntSuffixArray::<u32, usize>::new(&[3, 1, 2], 4);
+    /// let word: &[u32] = sa.word();
+    /// assert_eq!(&[3, 1, 2, 0], word);
+    /// ```
+    #[inline]
+    pub fn word(&self) -> &[S] {
+        &self.word
+    }
+
+    /// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+    /// Construct LCP. Complexity O(n)
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let lcp = IntSuffixArray::<u32, usize>::new(&[3, 1, 2], 4).lcp();
+    /// ```
+    pub fn lcp(&self) -> LCP<T> {
+        lcp_kasai(self.text(), &self.sa)
+    }
+
+    /// Find substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let find: Option<usize> = IntSuffixArray::<u32, usize>::new(&[3, 1, 2], 4).find(&[1, 2]);
+    /// assert_eq!(find, Some(1));
+    /// ```
+    #[inline]
+    pub fn find(&self, find: &[S]) -> Option<T> {
+        let (start, end) = self.find_pos(find);
+        if start >= end {
+            return None;
+        }
+        Some(self.sa[start])
+    }
+
+    /// Find all substr. Complexity O(|find| * log(|word|))
+    /// ```
+    /// use suff_collections::int_array::*;
+    ///
+    /// let sa = IntSuffixArray::<u32, usize>::new(&[3, 1, 3, 1], 4);
+    /// let find: &[usize] = sa.find_all(&[3, 1]);
+    /// assert_eq!(find, &[2, 0]);
+    /// ```
+    #[inline]
+    pub fn find_all(&self, find: &[S]) -> &[T] {
+        let (start, end) = self.find_pos(find);
+        &self.sa[start..end]
+    }
+
+    // O(|find| * log(|word|))
+    fn find_pos(&self, find: &[S]) -> (usize, usize) {
+        find_range(self.text(), &self.sa, find)
+    }
+
+    // word without sentinel
+    #[inline]
+    fn text(&self) -> &[S] {
+        &self.word[..self.word.len().saturating_sub(1)]
+    }
+}
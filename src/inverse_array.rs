@@ -0,0 +1,109 @@
+//! Inverse suffix array. rank\[sa\[i\]\] = i, so rank(pos) is the index of suffix pos in the suffix array
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{array::*, inverse_array::*};
+//!
+//! let sa = SuffixArray::<usize>::new("abracadabra");
+//!
+//! // construct inverse suffix array. Complexity O(n)
+//! let inverse: InverseSuffixArray<usize> = sa.inverse();
+//!
+//! // where does suffix 7 sit in sorted order
+//! let rank: usize = inverse.rank(7);
+//! assert_eq!(sa.suffix_array()[rank], 7);
+//!
+//! // construct lcp without computing the rank again
+//! let lcp = sa.lcp_with_inverse(&inverse);
+//! ```
+
+use crate::array::build_suffix_array::SuffixIndices;
+use alloc::vec::Vec;
+use core::slice::Iter;
+
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct InverseSuffixArray<T: SuffixIndices<T>>(Vec<T>);
+impl<T: SuffixIndices<T>> InverseSuffixArray<T> {
+    pub(crate) fn new(rank: Vec<T>) -> Self {
+        Self(rank)
+    }
+
+    /// Index of suffix pos in the suffix array
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("word");
+    /// let inverse = sa.inverse();
+    /// assert_eq!(sa.suffix_array()[inverse.rank(2)], 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if pos >= word.len() with the sentinel.
+    #[inline]
+    pub fn rank(&self, pos: usize) -> T {
+        self.0[pos]
+    }
+
+    /// Return ref to inner slice
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let inverse = SuffixArray::<usize>::new("word").inverse();
+    /// let inner: &[usize] = inverse.inner();
+    /// ```
+    #[inline]
+    pub fn inner(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Move inner vec
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let inverse = SuffixArray::<usize>::new("word").inverse();
+    /// let inner: Vec<usize> = inverse.owned();
+    /// ```
+    #[inline]
+    pub fn owned(self) -> Vec<T> {
+        self.0
+    }
+
+    /// Return iterator to inner slice
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let inverse = SuffixArray::<usize>::new("word").inverse();
+    /// let copy = inverse.iter().map(|&x| x).collect::<Vec<_>>();
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Return length of inverse suffix array
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let inverse = SuffixArray::<usize>::new("word").inverse();
+    /// assert_eq!(inverse.len(), "word\0".len());
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the inverse suffix array contains no elements.
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let inverse = SuffixArray::<usize>::new("word").inverse();
+    /// assert_eq!(inverse.is_empty(), false);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
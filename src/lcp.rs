@@ -1,13 +1,46 @@
 //! lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) and lcp.len() == sa.len()
+//!
+//! `LcpRmq` answers longest common extension queries of two suffixes in O(1)
+//!
+//! `LcpInterval` is the interval of the suffix array with the common prefix,
+//! it corresponds to the node of the suffix tree. `LCP::bottom_up` traverses
+//! lcp-intervals in post-order like the internal nodes of the suffix tree
 
-use crate::array::build_suffix_array::SuffixIndices;
-use alloc::vec::Vec;
+use crate::{
+    array::{build_suffix_array::SuffixIndices, SuffixArray},
+    inverse_array::InverseSuffixArray,
+};
+use alloc::{borrow::Cow, vec::Vec};
 use core::ops::Index;
 use core::slice::{Iter, SliceIndex};
+use core::{
+    cmp::{max, min},
+    ops::Range,
+};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[repr(transparent)]
 pub struct LCP<T: SuffixIndices<T>>(Vec<T>);
+
+#[cfg(feature = "serde")]
+impl<'de, T: SuffixIndices<T> + serde::Deserialize<'de>> serde::Deserialize<'de> for LCP<T> {
+    /// Deserialize lcp and check its bounds. Complexity O(n).
+    /// Values are not compared with the suffix array, so lcp must be
+    /// loaded together with the suffix array it was built from
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let lcp = Vec::<T>::deserialize(deserializer)?;
+        if !lcp.is_empty() && lcp[0].to_usize() != 0 {
+            return Err(serde::de::Error::custom("lcp[0] is not 0"));
+        }
+        // common prefix of two different suffixes is shorter than the word without the sentinel
+        if lcp.iter().skip(1).any(|x| x.to_usize() + 1 >= lcp.len()) {
+            return Err(serde::de::Error::custom("lcp value is out of the word"));
+        }
+        Ok(Self(lcp))
+    }
+}
 impl<T: SuffixIndices<T>> LCP<T> {
     pub(crate) fn new(lcp: Vec<T>) -> Self {
         Self(lcp)
@@ -103,6 +136,26 @@ impl<T: SuffixIndices<T>> LCP<T> {
         self.0.get_unchecked(index)
     }
 
+    /// Iterator over lcp-intervals in post-order (children before the parent). Complexity O(n)
+    /// ```
+    /// use suff_collections::{array::*, lcp::*};
+    ///
+    /// // sa = [6, 5, 3, 1, 0, 4, 2]
+    /// let sa = SuffixArray::<usize>::new("banana");
+    /// let intervals = sa
+    ///     .lcp()
+    ///     .bottom_up()
+    ///     .map(|(x, children)| (x.lcp(), x.lb(), x.rb(), children.len()))
+    ///     .collect::<Vec<_>>();
+    /// // "ana", "a", "na", root
+    /// assert_eq!(intervals, vec![(3, 2, 3, 0), (1, 1, 3, 1), (2, 5, 6, 0), (0, 0, 6, 2)]);
+    /// ```
+    /// Every item is the lcp-interval with its child lcp-intervals, leaves (singleton intervals)
+    /// are not included. Indices of intervals are indices of the suffix array. The root is the last item.
+    pub fn bottom_up(&self) -> LcpIntervals<'_, T> {
+        LcpIntervals::new(Cow::from(&self.0[..]))
+    }
+
     #[cfg(debug_assertions)]
     #[inline]
     pub(crate) unsafe fn idx_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> &mut I::Output {
@@ -123,3 +176,230 @@ impl<T: SuffixIndices<T>, I: SliceIndex<[T]>> Index<I> for LCP<T> {
         &self.0.index(index)
     }
 }
+
+/// Iterator over lcp-intervals in post-order, see `LCP::bottom_up`
+#[derive(Debug, Clone)]
+pub struct LcpIntervals<'t, T: SuffixIndices<T>> {
+    lcp: Cow<'t, [T]>,
+    i: usize,
+    lb: usize,
+    // the last popped interval if it is the child of the next pushed one
+    last: Option<LcpInterval>,
+    // (lcp, lb, child intervals)
+    stack: Vec<(usize, usize, Vec<LcpInterval>)>,
+}
+
+impl<'t, T: SuffixIndices<T>> LcpIntervals<'t, T> {
+    pub(crate) fn new(lcp: Cow<'t, [T]>) -> Self {
+        let stack = if lcp.is_empty() {
+            vec![]
+        } else {
+            vec![(0, 0, vec![])]
+        };
+        Self {
+            lcp,
+            i: 1,
+            lb: 0,
+            last: None,
+            stack,
+        }
+    }
+}
+
+impl<T: SuffixIndices<T>> Iterator for LcpIntervals<'_, T> {
+    type Item = (LcpInterval, Vec<LcpInterval>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.i <= self.lcp.len() {
+            // lcp after the end (None) is lower than any lcp
+            let cur = self.lcp.get(self.i).map(|x| x.to_usize());
+            let top = self.stack.last().map(|x| x.0);
+            if cur < top {
+                let (lcp, lb, children) = self.stack.pop().unwrap();
+                let interval = LcpInterval::new(lcp, lb, self.i - 1);
+                self.lb = lb;
+                match self.stack.last_mut() {
+                    Some(top) if cur <= Some(top.0) => top.2.push(interval),
+                    _ => self.last = Some(interval),
+                }
+                return Some((interval, children));
+            }
+            if let Some(cur) = cur.filter(|_| cur > top) {
+                let children = self.last.take().into_iter().collect();
+                self.stack.push((cur, self.lb, children));
+            }
+            self.i += 1;
+            self.lb = self.i - 1;
+        }
+        None
+    }
+}
+
+/// Sparse table of range minimum over lcp with the rank of suffixes
+#[derive(Debug, Clone)]
+pub struct LcpRmq<T: SuffixIndices<T>> {
+    rank: InverseSuffixArray<T>,
+    // table[k][i] = min(lcp[i..i + 2^k])
+    table: Vec<Vec<T>>,
+}
+
+impl<T: SuffixIndices<T>> LcpRmq<T> {
+    /// Construct sparse table from suffix array and its lcp. Complexity O(n * log(n))
+    /// ```
+    /// use suff_collections::{array::*, lcp::*};
+    ///
+    /// let sa = SuffixArray::<usize>::new("word");
+    /// let rmq = LcpRmq::new(&sa, &sa.lcp());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if lcp.len() != suffix array len.
+    pub fn new(sa: &SuffixArray<T>, lcp: &LCP<T>) -> Self {
+        Self::with_inverse(sa.inverse(), lcp)
+    }
+
+    /// Construct sparse table from the inverse suffix array and lcp. Complexity O(n * log(n))
+    /// ```
+    /// use suff_collections::{array::*, lcp::*};
+    ///
+    /// let sa = SuffixArray::<usize>::new("word");
+    /// let inverse = sa.inverse();
+    /// let lcp = sa.lcp_with_inverse(&inverse);
+    /// let rmq = LcpRmq::with_inverse(inverse, &lcp);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if lcp.len() != inverse.len().
+    pub fn with_inverse(inverse: InverseSuffixArray<T>, lcp: &LCP<T>) -> Self {
+        assert_eq!(inverse.len(), lcp.len());
+
+        let mut table = vec![lcp.inner().to_vec()];
+        let mut len = 1;
+        while 2 * len <= lcp.len() {
+            let prev = table.last().unwrap();
+            let next = prev
+                .iter()
+                .zip(&prev[len..])
+                .map(|(&a, &b)| min(a, b))
+                .collect();
+            table.push(next);
+            len *= 2;
+        }
+        Self {
+            rank: inverse,
+            table,
+        }
+    }
+
+    /// Longest common extension of suffixes i and j of the word
+    /// (length of the longest common prefix). Complexity O(1)
+    /// ```
+    /// use suff_collections::{array::*, lcp::*};
+    ///
+    /// let sa = SuffixArray::<usize>::new("abracadabra");
+    /// let rmq = LcpRmq::new(&sa, &sa.lcp());
+    /// assert_eq!(rmq.lce(0, 7), 4);
+    /// assert_eq!(rmq.lce(3, 5), 1);
+    /// assert_eq!(rmq.lce(1, 2), 0);
+    /// assert_eq!(rmq.lce(7, 7), 4);
+    /// ```
+    /// Positions are the same as in the suffix array. The sentinel is not counted
+    /// and does not match zero bytes of the word, so lce(i, i) is the length
+    /// of suffix i without the sentinel.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if i or j >= word.len() with the sentinel.
+    pub fn lce(&self, i: usize, j: usize) -> T {
+        if i == j {
+            assert!(i < self.rank.len());
+            return T::try_from(self.rank.len() - 1 - i).ok().unwrap();
+        }
+        let (ri, rj) = (self.rank.rank(i).to_usize(), self.rank.rank(j).to_usize());
+        self.min(min(ri, rj) + 1, max(ri, rj))
+    }
+
+    // min(lcp[from..=to])
+    #[inline]
+    fn min(&self, from: usize, to: usize) -> T {
+        let k = (usize::BITS - 1 - (to - from + 1).leading_zeros()) as usize;
+        min(self.table[k][from], self.table[k][to + 1 - (1 << k)])
+    }
+}
+
+/// lcp-interval \[lb..=rb\] of the suffix array. All suffixes in it have the common prefix of length lcp.
+/// The interval with lb == rb is a leaf, its lcp is the length of the suffix without the sentinel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcpInterval {
+    lcp: usize,
+    lb: usize,
+    rb: usize,
+}
+
+impl LcpInterval {
+    pub(crate) fn new(lcp: usize, lb: usize, rb: usize) -> Self {
+        Self { lcp, lb, rb }
+    }
+
+    /// Return length of the common prefix of all suffixes in the interval
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abab"));
+    /// assert_eq!(esa.root().unwrap().lcp(), 0);
+    /// ```
+    #[inline]
+    pub fn lcp(&self) -> usize {
+        self.lcp
+    }
+
+    /// Return index of the first suffix in the suffix array
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abab"));
+    /// assert_eq!(esa.root().unwrap().lb(), 0);
+    /// ```
+    #[inline]
+    pub fn lb(&self) -> usize {
+        self.lb
+    }
+
+    /// Return index of the last suffix in the suffix array
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abab"));
+    /// assert_eq!(esa.root().unwrap().rb(), 4);
+    /// ```
+    #[inline]
+    pub fn rb(&self) -> usize {
+        self.rb
+    }
+
+    /// Returns `true` if the interval contains one suffix
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abab"));
+    /// assert_eq!(esa.root().unwrap().is_leaf(), false);
+    /// ```
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.lb == self.rb
+    }
+
+    /// Return interval as range of the suffix array
+    /// ```
+    /// use suff_collections::{array::*, enhanced_array::*};
+    ///
+    /// let esa = EnhancedSuffixArray::new(SuffixArray::<usize>::new("abab"));
+    /// assert_eq!(esa.root().unwrap().as_range(), 0..5);
+    /// ```
+    #[inline]
+    pub fn as_range(&self) -> Range<usize> {
+        self.lb..self.rb + 1
+    }
+}
@@ -1,4 +1,8 @@
 //! no_std support
+//!
+//! Optional `serde` feature implements `Serialize` and `Deserialize` for `SuffixArray`, `LCP` and `SuffixTree`
+//!
+//! Optional `rayon` feature adds the parallel construction `SuffixArray::new_par`, `SuffixArray::from_bytes_par` and `SuffixArray::lcp_phi_par`
 
 //! # Suffix Array
 //! Implementation of the [suffix array](https://www.researchgate.net/profile/Daricks_Wai_Hong_Chan/publication/221577802_Linear_Suffix_Array_Construction_by_Almost_Pure_Induced-Sorting/links/00b495318a21ba484f000000/Linear-Suffix-Array-Construction-by-Almost-Pure-Induced-Sorting.pdf?origin=publication_detail)
@@ -40,8 +44,27 @@
 //! // O(|word|)
 //! let res_all: &[usize] = sa.find_all_big(&sa.lcp(), find);
 //!
+//! // Burrows–Wheeler transform, inverse_bwt(&bwt, primary) restores the word
+//! let (bwt, primary): (Vec<u8>, usize) = sa.bwt();
+//!
+//! // longest substr which occurs at least twice and all maximal repeats with length >= 2
+//! let lrs = sa.longest_repeated_substring();
+//! let repeats = sa.maximal_repeats(2).collect::<Vec<_>>();
+//!
+//! // LZ77 factorization, lz77_decode(&factors) restores the word
+//! let factors = sa.lz77_factorize();
+//!
+//! // versioned binary format, SuffixArrayRef::from_bytes searches over it without copying
+//! let bytes: Vec<u8> = sa.to_binary(Some(&sa.lcp()));
+//!
 //! // convert suffix array to suffix tree
 //! let st = SuffixTree::from(sa);
+//!
+//! // construct suffix array over arbitrary bytes
+//! // let sa = SuffixArray::<usize>::from_bytes_stack(bytes);
+//! let bytes: &[u8] = &[0xff, 0x00, 0x7f];
+//! let sa = SuffixArray::<usize>::from_bytes(bytes);
+//! let res_all: &[usize] = sa.find_all(&[0x00, 0x7f]);
 //! ```
 
 //! # Suffix Tree
@@ -80,30 +103,37 @@
 #![no_std]
 #[macro_use(vec)]
 extern crate alloc;
+pub mod any_array;
 pub mod array;
+pub mod binary;
+pub mod bwt;
+pub mod enhanced_array;
+pub mod error;
+pub mod fm_index;
+pub mod gen_array;
+pub mod int_array;
+pub mod inverse_array;
 pub mod lcp;
+pub mod lz77;
+pub mod repeats;
 pub mod tree;
 
 pub(crate) mod bit;
+#[cfg(feature = "rayon")]
+pub(crate) mod par_build;
 
-use alloc::borrow::{Cow, ToOwned};
+use alloc::borrow::Cow;
 use alloc::vec::Vec;
-use core::str;
-fn canonic_word(word: &str) -> Cow<'_, str> {
-    if word.as_bytes().last() == Some(&0) {
+use array::build_suffix_array::Zero;
+fn canonic_word<S: Zero + Copy + Eq>(word: &[S]) -> Cow<'_, [S]> {
+    if word.last() == Some(&S::zero()) {
         Cow::from(word)
     } else {
-        Cow::from(
-            str::from_utf8(
-                &word
-                    .as_bytes()
-                    .iter()
-                    .chain(&[0])
-                    .copied()
-                    .collect::<Vec<_>>(),
-            )
-            .unwrap()
-            .to_owned(),
-        )
+        Cow::from(word.iter().chain(&[S::zero()]).copied().collect::<Vec<_>>())
     }
 }
+
+// the trailing 0 of the word is a symbol of the word, so the sentinel is always added
+fn with_sentinel<S: Zero + Copy>(word: &[S]) -> Cow<'_, [S]> {
+    Cow::from(word.iter().chain(&[S::zero()]).copied().collect::<Vec<_>>())
+}
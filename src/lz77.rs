@@ -0,0 +1,116 @@
+//! Implementation of the [LZ77 factorization](https://en.wikipedia.org/wiki/LZ77_and_LZ78)
+//! from the suffix array. Every factor is the longest prefix of the rest of the word
+//! which occurs before it, or the symbol which does not occur before.
+//! Sources are found by the previous and the next smaller values of the suffix array
+//! ([Kärkkäinen, Kempa, Puglisi](https://arxiv.org/abs/1212.2952)) in linear time
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{array::*, lz77::*};
+//!
+//! let sa = SuffixArray::<usize>::new("abababc");
+//!
+//! // factors of the word without the sentinel. Complexity O(n)
+//! let factors: Vec<Factor> = sa.lz77_factorize();
+//! assert_eq!(
+//!     factors,
+//!     vec![
+//!         Factor::Literal(b'a'),
+//!         Factor::Literal(b'b'),
+//!         Factor::Copy { source: 0, len: 4 },
+//!         Factor::Literal(b'c'),
+//!     ]
+//! );
+//!
+//! // restore the word. Complexity O(n)
+//! let word: Vec<u8> = lz77_decode(&factors);
+//! assert_eq!(word, b"abababc");
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::array::{
+    build_suffix_array::{Max, SuffixIndices},
+    count_eq,
+};
+
+/// Factor of the LZ77 factorization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Factor {
+    /// The symbol which does not occur before
+    Literal(u8),
+    /// Copy of len symbols from source < start of the factor,
+    /// the copy may overlap the factor
+    Copy { source: usize, len: usize },
+}
+
+/// Restore the word from LZ77 factors. Complexity O(n)
+/// ```
+/// use suff_collections::lz77::*;
+///
+/// let factors = [Factor::Literal(b'a'), Factor::Copy { source: 0, len: 3 }];
+/// assert_eq!(lz77_decode(&factors), b"aaaa");
+/// ```
+///
+/// # Panics
+///
+/// This function will panic if the source of a copy is not before the start of its factor.
+pub fn lz77_decode(factors: &[Factor]) -> Vec<u8> {
+    let mut word = Vec::new();
+    for &factor in factors {
+        match factor {
+            Factor::Literal(x) => word.push(x),
+            Factor::Copy { source, len } => {
+                assert!(source < word.len());
+                // the copy may overlap the factor, so it goes symbol by symbol
+                for i in source..source + len {
+                    word.push(word[i]);
+                }
+            }
+        }
+    }
+    word
+}
+
+// text is the word without the sentinel and sa is the suffix array of the word
+pub(crate) fn lz77_factorize<T: SuffixIndices<T>>(text: &[u8], sa: &[T]) -> Vec<Factor> {
+    let empty = <T as Max>::max();
+    // psv[x] and nsv[x] are the nearest suffixes before and after x in the suffix array
+    // order which start before x, they are the closest to x lexicographically
+    let (mut psv, mut nsv) = (vec![empty; sa.len()], vec![empty; sa.len()]);
+    let mut stack: Vec<T> = Vec::new();
+    for &x in sa {
+        while let Some(&top) = stack.last().filter(|&&top| top > x) {
+            nsv[top.to_usize()] = x;
+            stack.pop();
+        }
+        if let Some(&top) = stack.last() {
+            psv[x.to_usize()] = top;
+        }
+        stack.push(x);
+    }
+
+    let mut factors = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let (source, len) = [psv[i], nsv[i]]
+            .iter()
+            .filter(|&&x| x != empty)
+            .map(|&x| {
+                (
+                    x.to_usize(),
+                    count_eq(&text[i..], &text[x.to_usize()..], 0usize),
+                )
+            })
+            .fold((0, 0), |best, x| if x.1 > best.1 { x } else { best });
+        if len == 0 {
+            factors.push(Factor::Literal(text[i]));
+            i += 1;
+        } else {
+            factors.push(Factor::Copy { source, len });
+            i += len;
+        }
+    }
+    factors
+}
@@ -0,0 +1,308 @@
+//! Parallel SA-IS (Labeit, Shun, Blelloch "Parallel Lightweight Wavelet Tree,
+//! Suffix Array and FM-Index Construction"). Type classification, LMS extraction,
+//! bucket counting and naming run in parallel. Induced sorting scans the suffix array
+//! by blocks: symbols and types of the predecessors of a block are read in parallel
+//! into the buffer, then the block is written sequentially.
+//! The result is the same as of the sequential construction.
+//!
+//! Parallel lcp through the permuted lcp (Φ array), the word is split into chunks
+//! which start from the lcp values at their boundaries.
+
+use alloc::vec::Vec;
+use core::cmp::min;
+use rayon::prelude::*;
+
+use crate::array::{
+    build_suffix_array::{Max, SuffixIndices, ToUsize},
+    count_eq,
+};
+
+// length of the chunk of the word processed by one task
+const CHUNK: usize = 1 << 16;
+// length of the block of the induced sorting
+const BLOCK: usize = 1 << 14;
+
+// Build suffix array of s. s.last() must be the unique smallest symbol and max(s) < dict_size.
+// T::max() is never a position because s.len() < T::max()
+pub(crate) fn suffix_array<S, T>(s: &[S], dict_size: usize) -> Vec<T>
+where
+    S: ToUsize + Ord + Copy + Sync,
+    T: SuffixIndices<T>,
+{
+    let n = s.len();
+    if n == 1 {
+        return vec![T::zero()];
+    }
+    let empty = <T as Max>::max();
+    let t = calc_type(s);
+    let is_lms = |i: usize| i > 0 && t[i] && !t[i - 1];
+
+    // lms => ... L S ... (... > <= ...)
+    let idx_lms = (1..n)
+        .into_par_iter()
+        .filter(|&i| is_lms(i))
+        .map(|i| T::try_from(i).ok().unwrap())
+        .collect::<Vec<_>>();
+    let bucket_starts = bucket_starts(s, dict_size);
+
+    // sort lms substrings
+    let mut sa = vec![empty; n];
+    induced_sort(s, &t, &bucket_starts, &idx_lms, &mut sa);
+    let sorted_sublms = sa
+        .par_iter()
+        .copied()
+        .filter(|&x| is_lms(x.to_usize()))
+        .collect::<Vec<_>>();
+
+    // name lms substrings, the sentinel gets the name 0
+    let is_new = (0..sorted_sublms.len())
+        .into_par_iter()
+        .map(|i| {
+            i == 0
+                || !sublms_is_eq(
+                    s,
+                    &t,
+                    sorted_sublms[i - 1].to_usize(),
+                    sorted_sublms[i].to_usize(),
+                )
+        })
+        .collect::<Vec<_>>();
+    // lms positions differ by at least 2
+    let mut names = vec![T::zero(); n / 2 + 1];
+    let mut name = 0;
+    for (&x, &is_new) in sorted_sublms.iter().zip(&is_new) {
+        name += is_new as usize;
+        names[x.to_usize() / 2] = T::try_from(name - 1).ok().unwrap();
+    }
+
+    let sa_lms = if name == idx_lms.len() {
+        // all lms substrings are unique so they are sorted as lms suffixes
+        sorted_sublms
+    } else {
+        let new_s_idx = idx_lms
+            .par_iter()
+            .map(|&x| names[x.to_usize() / 2])
+            .collect::<Vec<_>>();
+        drop(names);
+        suffix_array::<T, T>(&new_s_idx, name)
+            .par_iter()
+            .map(|&x| idx_lms[x.to_usize()])
+            .collect()
+    };
+
+    sa.par_iter_mut().for_each(|x| *x = empty);
+    induced_sort(s, &t, &bucket_starts, &sa_lms, &mut sa);
+    sa
+}
+
+// t[i] is true if suffix i is S-type
+fn calc_type<S: Ord + Copy + Sync>(s: &[S]) -> Vec<bool> {
+    let n = s.len();
+    let mut t = vec![true; n];
+    t.par_chunks_mut(CHUNK).enumerate().for_each(|(k, t)| {
+        let from = k * CHUNK;
+        // the type of the last symbol of the chunk is fixed below if it equals the next one
+        for i in (from..min(from + t.len(), n - 1)).rev() {
+            let next = t.get(i + 1 - from).copied().unwrap_or(false);
+            t[i - from] = s[i] < s[i + 1] || (s[i] == s[i + 1] && next);
+        }
+    });
+    // a run of equal symbols has the type of the first following different symbol.
+    // Going from the right end the type at the boundary is already correct
+    for boundary in (CHUNK..n).step_by(CHUNK).rev() {
+        let from = boundary - CHUNK;
+        let mut i = boundary;
+        while i > from && s[i - 1] == s[boundary] {
+            t[i - 1] = t[boundary];
+            i -= 1;
+        }
+    }
+    t
+}
+
+// bucket_starts[x] is the number of symbols less than x
+fn bucket_starts<S: ToUsize + Copy + Sync>(s: &[S], dict_size: usize) -> Vec<usize> {
+    let mut cnt = if dict_size <= CHUNK {
+        s.par_chunks(CHUNK)
+            .fold(
+                || vec![0; dict_size],
+                |mut cnt, chunk| {
+                    chunk.iter().for_each(|&x| cnt[x.to_usize()] += 1);
+                    cnt
+                },
+            )
+            .reduce(
+                || vec![0; dict_size],
+                |mut acc, cnt| {
+                    acc.iter_mut().zip(cnt).for_each(|(acc, x)| *acc += x);
+                    acc
+                },
+            )
+    } else {
+        // the alphabet of the reduced word is too big for the count per chunk
+        let mut cnt = vec![0; dict_size];
+        s.iter().for_each(|&x| cnt[x.to_usize()] += 1);
+        cnt
+    };
+    cnt.iter_mut().fold(0, |acc, x| {
+        let cnt = *x;
+        *x = acc;
+        acc + cnt
+    });
+    cnt.push(s.len());
+    cnt
+}
+
+// sa must be filled with T::max()
+fn induced_sort<S, T>(s: &[S], t: &[bool], bucket_starts: &[usize], sa_lms: &[T], sa: &mut [T])
+where
+    S: ToUsize + Copy + Sync,
+    T: SuffixIndices<T>,
+{
+    let dict_size = bucket_starts.len() - 1;
+    let empty = <T as Max>::max();
+    // bucket of the predecessor of suffix x if it has the given type
+    let induced = |x: T, is_s: bool| -> Option<usize> {
+        if x == empty || x == T::zero() {
+            return None;
+        }
+        let prev = x.to_usize() - 1;
+        if t[prev] == is_s {
+            Some(s[prev].to_usize())
+        } else {
+            None
+        }
+    };
+
+    let mut ends = bucket_starts[1..].to_vec();
+    sa_lms.iter().rev().for_each(|&x| {
+        let end = &mut ends[s[x.to_usize()].to_usize()];
+        *end -= 1;
+        sa[*end] = x;
+    });
+
+    let mut buf = Vec::with_capacity(BLOCK);
+    // L-type suffixes are placed to the start of buckets from left to right
+    let mut starts = bucket_starts[..dict_size].to_vec();
+    for from in (0..sa.len()).step_by(BLOCK) {
+        let to = min(from + BLOCK, sa.len());
+        read_block(&sa[from..to], &mut buf, |x| induced(x, false));
+        for i in from..to {
+            if let Some(bucket) = cached(sa[i], buf[i - from], |x| induced(x, false)) {
+                sa[starts[bucket]] = sa[i] - T::one();
+                starts[bucket] += 1;
+            }
+        }
+    }
+
+    // S-type suffixes are placed to the end of buckets from right to left
+    let mut ends = bucket_starts[1..].to_vec();
+    for to in (0..sa.len()).rev().step_by(BLOCK).map(|x| x + 1) {
+        let from = to.saturating_sub(BLOCK);
+        read_block(&sa[from..to], &mut buf, |x| induced(x, true));
+        for i in (from..to).rev() {
+            if let Some(bucket) = cached(sa[i], buf[i - from], |x| induced(x, true)) {
+                ends[bucket] -= 1;
+                sa[ends[bucket]] = sa[i] - T::one();
+            }
+        }
+    }
+}
+
+// read in parallel buckets of predecessors of the block
+#[inline]
+fn read_block<T: SuffixIndices<T>>(
+    block: &[T],
+    buf: &mut Vec<(T, Option<usize>)>,
+    induced: impl Fn(T) -> Option<usize> + Sync,
+) {
+    block
+        .par_iter()
+        .map(|&x| (x, induced(x)))
+        .collect_into_vec(buf);
+}
+
+// the value in the block can be written after the block was read
+#[inline]
+fn cached<T: SuffixIndices<T>>(
+    x: T,
+    (read, bucket): (T, Option<usize>),
+    induced: impl Fn(T) -> Option<usize>,
+) -> Option<usize> {
+    if x == read {
+        bucket
+    } else {
+        induced(x)
+    }
+}
+
+// x and prev are lms positions
+fn sublms_is_eq<S: Eq>(s: &[S], t: &[bool], x: usize, prev: usize) -> bool {
+    let is_lms = |i: usize| t[i] && !t[i - 1];
+    for i in 0.. {
+        // the sentinel is the unique lms substring so the comparison stops before the end
+        if s[x + i] != s[prev + i] || t[x + i] != t[prev + i] {
+            return false;
+        }
+        if i != 0 && (is_lms(x + i) || is_lms(prev + i)) {
+            return true;
+        }
+    }
+    false
+}
+
+// pointer to the array which is written in parallel at different positions
+struct SyncPtr<T>(*mut T);
+unsafe impl<T: Send> Send for SyncPtr<T> {}
+unsafe impl<T: Send> Sync for SyncPtr<T> {}
+
+// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1\]) && lcp.len() == sa.len()
+pub(crate) fn lcp_phi<S: Eq + Sync, T: SuffixIndices<T>>(word: &[S], sa: &[T]) -> Vec<T> {
+    let empty = <T as Max>::max();
+    // phi[sa[i]] = sa[i - 1] and phi[sa[0]] = T::max() because sa.len() < T::max()
+    let mut plcp = vec![empty; sa.len()];
+    let phi = SyncPtr(plcp.as_mut_ptr());
+    sa.par_windows(2).for_each(|x| {
+        // safe because sa is a permutation of 0..sa.len(), so every position is written once
+        unsafe { *phi.0.add(x[1].to_usize()) = x[0] }
+    });
+
+    // plcp[i + 1] >= plcp[i] - 1, so plcp at the start of the chunk is at least plcp at
+    // the start of the previous one minus CHUNK. The sequential pass over chunk starts
+    // is O(n) in total and every chunk continues from its exact start value
+    let mut seeds = Vec::with_capacity(plcp.len().div_ceil(CHUNK));
+    let mut pref_len = 0usize;
+    for i in (0..plcp.len()).step_by(CHUNK) {
+        pref_len = match plcp[i] {
+            x if x == empty => 0,
+            x => count_eq(
+                &word[i..],
+                &word[x.to_usize()..],
+                pref_len.saturating_sub(CHUNK),
+            ),
+        };
+        seeds.push(T::try_from(pref_len).ok().unwrap());
+    }
+
+    plcp.par_chunks_mut(CHUNK)
+        .zip(seeds)
+        .enumerate()
+        .for_each(|(k, (chunk, mut pref_len))| {
+            for (i, x) in chunk
+                .iter_mut()
+                .enumerate()
+                .map(|(i, x)| (k * CHUNK + i, x))
+            {
+                if *x == empty {
+                    pref_len = T::zero();
+                } else {
+                    pref_len = count_eq(&word[i..], &word[x.to_usize()..], pref_len);
+                }
+                *x = pref_len;
+                if pref_len > T::zero() {
+                    pref_len -= T::one();
+                }
+            }
+        });
+    sa.par_iter().map(|&x| plcp[x.to_usize()]).collect()
+}
@@ -0,0 +1,158 @@
+//! Repeats of the word. A repeat is a substr which occurs at least twice.
+//! It is maximal if its entries can not be extended by the same symbol
+//! to the left or to the right. Maximal repeats are the lcp-intervals
+//! (internal nodes of the suffix tree) whose suffixes are preceded
+//! by different symbols (left-maximal)
+//!
+//! A maximal repeat is supermaximal if it is not a substr of another repeat,
+//! and near-supermaximal if at least one of its entries is not inside
+//! an entry of another maximal repeat
+
+//! # Examples
+//!
+//! ```
+//! use suff_collections::{array::*, repeats::*, tree::*};
+//!
+//! let sa = SuffixArray::<usize>::new("abcxabcyabc");
+//!
+//! // longest substr which occurs at least twice. O(n)
+//! let lrs: Repeat<usize> = sa.longest_repeated_substring().unwrap();
+//! assert_eq!(lrs.as_bytes(), b"abc");
+//! assert_eq!(lrs.positions(), &[8, 0, 4]);
+//!
+//! // all maximal repeats with length >= 1. O(n + total number of entries)
+//! for repeat in sa.maximal_repeats(1) {
+//!     let (repeat, count): (&[u8], usize) = (repeat.as_bytes(), repeat.count());
+//! }
+//!
+//! // near-supermaximal repeats with the flag that the repeat is supermaximal. O(n)
+//! let supermaximal = sa
+//!     .supermaximal_repeats(1)
+//!     .filter(|(_, is_supermaximal)| *is_supermaximal)
+//!     .map(|(x, _)| x.as_bytes())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(supermaximal, vec![b"abc"]);
+//!
+//! // the same over suffix tree
+//! let st = SuffixTree::new("abcxabcyabc");
+//! assert_eq!(st.longest_repeated_substring().unwrap().as_bytes(), b"abc");
+//! ```
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use crate::array::build_suffix_array::SuffixIndices;
+
+/// Repeat of the word with positions of all its entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repeat<'w, T: SuffixIndices<T>> {
+    repeat: &'w [u8],
+    positions: Cow<'w, [T]>,
+}
+
+impl<'w, T: SuffixIndices<T>> Repeat<'w, T> {
+    pub(crate) fn new(repeat: &'w [u8], positions: Cow<'w, [T]>) -> Self {
+        Self { repeat, positions }
+    }
+
+    /// Return ref on bytes of the repeat
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// let lrs = sa.longest_repeated_substring().unwrap();
+    /// assert_eq!(lrs.as_bytes(), b"ab");
+    /// ```
+    #[inline]
+    pub fn as_bytes(&self) -> &'w [u8] {
+        self.repeat
+    }
+
+    /// Return positions of all entries in the lexicographic order of suffixes
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// let lrs = sa.longest_repeated_substring().unwrap();
+    /// assert_eq!(lrs.positions(), &[2, 0]);
+    /// ```
+    #[inline]
+    pub fn positions(&self) -> &[T] {
+        &self.positions
+    }
+
+    /// Number of entries of the repeat
+    /// ```
+    /// use suff_collections::array::*;
+    ///
+    /// let sa = SuffixArray::<usize>::new("abab");
+    /// assert_eq!(sa.longest_repeated_substring().unwrap().count(), 2);
+    /// ```
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.positions.len()
+    }
+}
+
+// breaks[i] is the number of j in 1..=i such that the suffixes sa[j - 1] and sa[j]
+// are preceded by different symbols or one of them is the whole word
+#[derive(Debug, Clone)]
+pub(crate) struct LeftBreaks<T: SuffixIndices<T>>(Vec<T>);
+
+impl<T: SuffixIndices<T>> LeftBreaks<T> {
+    pub(crate) fn new(text: &[u8], sa: &[T]) -> Self {
+        let left = |x: T| x.to_usize().checked_sub(1).map(|x| text[x]);
+        let mut breaks = Vec::with_capacity(sa.len());
+        let mut cnt = T::zero();
+        for i in 0..sa.len() {
+            if i > 0 {
+                let (prev, cur) = (left(sa[i - 1]), left(sa[i]));
+                if prev.is_none() || cur.is_none() || prev != cur {
+                    cnt += T::one();
+                }
+            }
+            breaks.push(cnt);
+        }
+        Self(breaks)
+    }
+
+    // suffixes of the interval lb..=rb are preceded by at least two different symbols
+    // or one of them is the whole word
+    #[inline]
+    pub(crate) fn is_left_maximal(&self, lb: usize, rb: usize) -> bool {
+        self.0[rb] != self.0[lb]
+    }
+}
+
+// Neighbours in the suffix array order with the same preceding symbol.
+// prev[i] is the index of the previous one + 1 or 0, next[i] is the index of the next one
+// or sa.len(). The suffix which is the whole word has no neighbours
+#[derive(Debug, Clone)]
+pub(crate) struct LeftNeighbours<T: SuffixIndices<T>> {
+    prev: Vec<T>,
+    next: Vec<T>,
+}
+
+impl<T: SuffixIndices<T>> LeftNeighbours<T> {
+    pub(crate) fn new(text: &[u8], sa: &[T]) -> Self {
+        let n = T::try_from(sa.len()).ok().unwrap();
+        let (mut prev, mut next) = (vec![T::zero(); sa.len()], vec![n; sa.len()]);
+        let mut last = [None; 256];
+        for (i, &x) in sa.iter().enumerate() {
+            if let Some(x) = x.to_usize().checked_sub(1) {
+                let last = &mut last[text[x] as usize];
+                if let Some(j) = *last {
+                    prev[i] = T::try_from(j + 1).ok().unwrap();
+                    next[j] = T::try_from(i).ok().unwrap();
+                }
+                *last = Some(i);
+            }
+        }
+        Self { prev, next }
+    }
+
+    // the preceding symbol of suffix i is unique in the interval lb..=rb
+    #[inline]
+    pub(crate) fn is_left_unique(&self, i: usize, lb: usize, rb: usize) -> bool {
+        self.prev[i].to_usize() <= lb && self.next[i].to_usize() > rb
+    }
+}
@@ -39,17 +39,29 @@
 //!
 //! // convert online suffix tree to suffix tree
 //! let st: SuffixTree = ost.finish();
+//!
+//! // construct generalized suffix tree over several strings
+//! let gst = GeneralizedSuffixTree::new(&["Some word", "other word"]);
+//!
+//! // all entries of 'find' as (string index, offset)
+//! let res_all: Vec<(usize, usize)> = gst.find_all(find);
+//!
+//! // longest substr which is contained in at least 2 strings
+//! let lcs: &str = gst.longest_common_substring(2);
 //! ```
 
 #![forbid(unsafe_code)]
 
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::{borrow::Cow, borrow::ToOwned, string::String, vec::Vec};
-use core::{fmt::Write, format_args, option::Option, str};
+use core::{fmt::Write, format_args, iter, ops::Range, option::Option, str};
 
-use crate::{array::build_suffix_array::SuffixIndices, array::*, lcp::*};
+use crate::{
+    array::build_suffix_array::SuffixIndices, array::*, error::SuffixError, lcp::*, repeats::*,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     link: Option<NodeIdx>,
     parent: NodeIdx,
@@ -135,9 +147,36 @@ struct State {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SuffixTree<'t> {
     word: Cow<'t, str>,
     tree: AloneSuffixTree,
+    // number of leaves in subtree of node
+    #[cfg_attr(feature = "serde", serde(skip))]
+    leaf_counts: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SuffixTree<'_> {
+    /// Deserialize suffix tree and check its structure. Complexity O(n)
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "SuffixTree")]
+        struct Raw {
+            word: String,
+            tree: AloneSuffixTree,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        raw.tree
+            .check(&raw.word)
+            .map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            word: Cow::from(raw.word),
+            leaf_counts: raw.tree.leaf_counts(),
+            tree: raw.tree,
+        })
+    }
 }
 
 impl<'t> SuffixTree<'t> {
@@ -160,6 +199,7 @@ impl<'t> SuffixTree<'t> {
                         pos: 0,
                     }],
                 },
+                leaf_counts: vec![0],
             };
         }
         let mut tree = Self {
@@ -173,8 +213,10 @@ impl<'t> SuffixTree<'t> {
                     pos: 0,
                 }],
             },
+            leaf_counts: Vec::new(),
         };
         tree.build_ukkonen();
+        tree.leaf_counts = tree.tree.leaf_counts();
         tree
     }
 
@@ -190,6 +232,38 @@ impl<'t> SuffixTree<'t> {
         self.tree.find(&self.word, find, false)
     }
 
+    /// Find all substr. Return positions in no particular order.
+    /// Complexity O(|find| + occ)
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let st = SuffixTree::new("abracadabra");
+    /// let mut find = st.find_all("abra").collect::<Vec<_>>();
+    /// find.sort();
+    /// assert_eq!(find, vec![0, 7]);
+    /// ```
+    pub fn find_all(&self, find: &str) -> impl Iterator<Item = usize> + '_ {
+        self.tree.find_all(&self.word, find, false)
+    }
+
+    /// Number of entries of substr. Complexity O(|find|)
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let st = SuffixTree::new("abracadabra");
+    /// assert_eq!(st.count("abra"), 2);
+    /// assert_eq!(st.count("abc"), 0);
+    /// ```
+    pub fn count(&self, find: &str) -> usize {
+        if find.is_empty() {
+            return 0;
+        }
+        match self.tree.locus(&self.word, find, false) {
+            Some((node_idx, _)) => self.leaf_counts[node_idx.unwrap()],
+            None => 0,
+        }
+    }
+
     /// lcp\[i\] = max_pref(sa\[i\], sa\[i - 1]\) && lcp.len() == sa.len()
     /// Construct LCP not recursive. Complexity O(n)
     /// ```
@@ -244,6 +318,23 @@ impl<'t> SuffixTree<'t> {
         }
     }
 
+    /// Construct LCP without panic. Complexity O(n)
+    /// ```
+    /// use suff_collections::{error::*, tree::*};
+    ///
+    /// let lcp = SuffixTree::new("word").try_lcp::<u8>();
+    /// assert!(lcp.is_ok());
+    /// let lcp = SuffixTree::new(&"a".repeat(300)).try_lcp::<u8>();
+    /// assert_eq!(lcp.unwrap_err(), SuffixError::IndexTooSmall { len: 300, max: 255 });
+    /// ```
+    /// Same as `lcp`, but return error if word.len() >= T::MAX
+    /// or the word contains '\0' not at the end.
+    pub fn try_lcp<T: SuffixIndices<T>>(&self) -> Result<LCP<T>, SuffixError> {
+        check_index_size::<T>(self.word.len())?;
+        check_sentinel(self.word.as_bytes())?;
+        Ok(self.lcp())
+    }
+
     /// if word is ascii then print data in .dot format for graphviz
     /// ```
     /// use suff_collections::tree::*;
@@ -388,6 +479,131 @@ impl<'t> SuffixTree<'t> {
         self.tree.try_to_node(current_node, transition)
     }
 
+    /// Longest substr which occurs at least twice. It is None if there is no repeat.
+    /// Complexity O(n)
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let st = SuffixTree::new("banana");
+    /// let lrs = st.longest_repeated_substring().unwrap();
+    /// assert_eq!(lrs.as_bytes(), b"ana");
+    /// assert_eq!(lrs.positions(), &[3, 1]);
+    /// assert!(SuffixTree::new("word").longest_repeated_substring().is_none());
+    /// ```
+    pub fn longest_repeated_substring(&self) -> Option<Repeat<'_, usize>> {
+        let (node_idx, depth, _) =
+            self.internal_nodes()
+                .into_iter()
+                .fold(None, |best, x| match best {
+                    Some((_, depth, _)) if depth >= x.1 => best,
+                    _ => Some(x),
+                })?;
+        Some(self.repeat(node_idx, depth))
+    }
+
+    /// Iterator over maximal repeats with length >= min_len. The order and positions
+    /// are the same as of `SuffixArray::maximal_repeats`. Complexity O(n + number of entries)
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let st = SuffixTree::new("abcxabcyabc");
+    /// let repeats = st
+    ///     .maximal_repeats(1)
+    ///     .map(|x| (x.as_bytes(), x.count()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(repeats, vec![(&b"abc"[..], 3)]);
+    /// ```
+    pub fn maximal_repeats(&self, min_len: usize) -> impl Iterator<Item = Repeat<'_, usize>> + '_ {
+        self.internal_nodes()
+            .into_iter()
+            .filter(move |&(_, depth, is_left_maximal)| depth >= min_len && is_left_maximal)
+            .map(move |(node_idx, depth, _)| self.repeat(node_idx, depth))
+    }
+
+    /// For each position i of query the length of the longest prefix of query\[i..\]
+    /// which occurs in the word and one of its entries as (len, pos), pos is 0 if len is 0.
+    /// Complexity O(|query|) with suffix links, a node without the link is rescanned from root
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let st = SuffixTree::new("abcab");
+    /// let ms = st.matching_statistics("cabd");
+    /// // "cab" at 2, "ab" at 0, "b" at 1, ""
+    /// assert_eq!(ms, vec![(3, 2), (2, 0), (1, 1), (0, 0)]);
+    /// ```
+    pub fn matching_statistics(&self, query: &str) -> Vec<(usize, usize)> {
+        let query = query.as_bytes();
+        let mut res = Vec::with_capacity(query.len());
+        // the match is the path to internal node of length depth and
+        // k symbols on the edge to the child by query[i + depth]
+        let (mut node_idx, mut depth, mut k) = (NodeIdx::root(), 0, 0);
+        for i in 0..query.len() {
+            while i + depth + k < query.len() {
+                let ch = query[i + depth + k];
+                let child_idx = if k == 0 {
+                    match self.try_to_node(node_idx, ch) {
+                        // terminal leaf has no symbols
+                        Some(x) if self.node(x).len > 0 => x,
+                        _ => break,
+                    }
+                } else {
+                    let child_idx = self.tree.to_node(node_idx, query[i + depth]);
+                    let child = self.node(child_idx);
+                    if k == child.len || self.word.as_bytes()[child.pos + k] != ch {
+                        break;
+                    }
+                    child_idx
+                };
+                k += 1;
+                let child = self.node(child_idx);
+                if k == child.len && !child.children.is_empty() {
+                    node_idx = child_idx;
+                    depth += k;
+                    k = 0;
+                }
+            }
+
+            res.push(match (depth + k, k) {
+                (0, _) => (0, 0),
+                (len, 0) => {
+                    let node = self.node(node_idx);
+                    (len, node.pos + node.len - depth)
+                }
+                (len, _) => {
+                    let child = self.tree.to_node(node_idx, query[i + depth]);
+                    (len, self.node(child).pos - depth)
+                }
+            });
+
+            // drop the first symbol of the match
+            if depth + k == 0 {
+                continue;
+            }
+            if self.is_root(node_idx) {
+                k -= 1;
+            } else if let Some(link) = self.node(node_idx).link {
+                node_idx = link;
+                depth -= 1;
+            } else {
+                k += depth - 1;
+                node_idx = NodeIdx::root();
+                depth = 0;
+            }
+            // skip and count down to the end of the match
+            while k > 0 {
+                let child_idx = self.tree.to_node(node_idx, query[i + 1 + depth]);
+                let child = self.node(child_idx);
+                if k < child.len || child.children.is_empty() {
+                    break;
+                }
+                node_idx = child_idx;
+                depth += child.len;
+                k -= child.len;
+            }
+        }
+        res
+    }
+
     // |word| + 1 == |leaf| leaf is word suffix + terminal leaf
     // |inner node| <= |word| + 1 because we do |word|
     //  operation (insert and add node) or add node + root node
@@ -433,6 +649,66 @@ impl<'t> SuffixTree<'t> {
 
         self.shrink_to_fit();
     }
+
+    // Non-root internal nodes in post-order with the length of their path and
+    // the flag that suffixes below are preceded by different symbols. Children
+    // are visited in the order of symbols, so it is the order of lcp-intervals
+    fn internal_nodes(&self) -> Vec<(NodeIdx, usize, bool)> {
+        let word = self.word.as_bytes();
+        // nodes in dfs order, so every child goes after its parent
+        let mut order = Vec::with_capacity(self.tree.nodes.len());
+        let mut depth = vec![0; self.tree.nodes.len()];
+        let mut stack = vec![NodeIdx::root()];
+        while let Some(node_idx) = stack.pop() {
+            order.push(node_idx);
+            for &child in self.node(node_idx).children.values() {
+                depth[child.unwrap()] = depth[node_idx.unwrap()] + self.node(child).len;
+                stack.push(child);
+            }
+        }
+
+        // the symbol which precedes all suffixes in subtree of node,
+        // None if they differ or one of suffixes is the whole word
+        let mut left = vec![None; self.tree.nodes.len()];
+        let mut res = Vec::new();
+        for &node_idx in order.iter().rev() {
+            let node = self.node(node_idx);
+            let i = node_idx.unwrap();
+            if node.children.is_empty() {
+                left[i] = (word.len() - depth[i]).checked_sub(1).map(|x| word[x]);
+                continue;
+            }
+            let mut children = node.children.values().map(|x| left[x.unwrap()]);
+            let first = children.next().flatten();
+            left[i] = first.filter(|_| children.all(|x| x == first));
+            if !self.is_root(node_idx) {
+                res.push((node_idx, depth[i], left[i].is_none()));
+            }
+        }
+        res
+    }
+
+    // Repeat of the path to the internal node with positions of leaves
+    // in the lexicographic order
+    fn repeat(&self, node_idx: NodeIdx, depth: usize) -> Repeat<'_, usize> {
+        let word = self.word.as_bytes();
+        let mut positions = Vec::with_capacity(self.leaf_counts[node_idx.unwrap()]);
+        let mut stack = vec![(node_idx, depth)];
+        while let Some((node_idx, depth)) = stack.pop() {
+            let node = self.node(node_idx);
+            if node.children.is_empty() {
+                positions.push(word.len() - depth);
+            }
+            stack.extend(
+                node.children
+                    .values()
+                    .rev()
+                    .map(|&x| (x, depth + self.node(x).len)),
+            );
+        }
+        let end = self.node(node_idx).pos + self.node(node_idx).len;
+        Repeat::new(&word[end - depth..end], Cow::from(positions))
+    }
 }
 
 impl<T: SuffixIndices<T>> From<SuffixArray<'_, T>> for SuffixTree<'_> {
@@ -446,6 +722,10 @@ impl<T: SuffixIndices<T>> From<SuffixArray<'_, T>> for SuffixTree<'_> {
     /// let st = SuffixArray::<usize>::new("word");
     /// let sa = SuffixTree::from(st);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the word of suffix array is not valid UTF-8.
     fn from(array: SuffixArray<T>) -> Self {
         let lcp = array.lcp();
         let (word, sa) = array.split_owned();
@@ -463,6 +743,7 @@ impl<T: SuffixIndices<T>> From<SuffixArray<'_, T>> for SuffixTree<'_> {
                     pos: 0,
                 }],
             },
+            leaf_counts: Vec::new(),
         };
 
         let tree_size = suff_tree.max_tree_size();
@@ -517,6 +798,7 @@ impl<T: SuffixIndices<T>> From<SuffixArray<'_, T>> for SuffixTree<'_> {
         }
 
         suff_tree.shrink_to_fit();
+        suff_tree.leaf_counts = suff_tree.tree.leaf_counts();
         suff_tree
     }
 }
@@ -612,6 +894,7 @@ impl OnlineSuffixTree {
 
         SuffixTree {
             word: Cow::from(self.word),
+            leaf_counts: self.tree.leaf_counts(),
             tree: self.tree,
         }
     }
@@ -628,6 +911,72 @@ impl OnlineSuffixTree {
     pub fn find(&self, find: &str) -> Option<usize> {
         self.tree.find(&self.word, find, true)
     }
+
+    /// Number of entries of substr. Complexity O(|find| + occ + |find| * k)
+    /// where k is the number of suffixes which are not leaves yet
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let mut st = OnlineSuffixTree::new();
+    /// st.add("abracadabra");
+    /// assert_eq!(st.count("abra"), 2);
+    /// assert_eq!(st.count("abc"), 0);
+    /// ```
+    pub fn count(&self, find: &str) -> usize {
+        if find.is_empty() {
+            return 0;
+        }
+        match self.tree.locus(&self.word, find, true) {
+            Some((node_idx, _)) => {
+                self.tree.leaf_count(node_idx)
+                    + self
+                        .implicit_suffixes()
+                        .filter(|&i| self.word.as_bytes()[i..].starts_with(find.as_bytes()))
+                        .count()
+            }
+            None => 0,
+        }
+    }
+
+    /// Find all substr. Return positions in no particular order.
+    /// Complexity O(|find| + occ + |find| * k)
+    /// where k is the number of suffixes which are not leaves yet
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let mut st = OnlineSuffixTree::new();
+    /// st.add("abracadabra");
+    /// let mut find = st.find_all("abra").collect::<Vec<_>>();
+    /// find.sort();
+    /// assert_eq!(find, vec![0, 7]);
+    /// ```
+    pub fn find_all(&self, find: &str) -> impl Iterator<Item = usize> + '_ {
+        let implicit = if find.is_empty() {
+            vec![]
+        } else {
+            self.implicit_suffixes()
+                .filter(|&i| self.word.as_bytes()[i..].starts_with(find.as_bytes()))
+                .collect()
+        };
+        self.tree.find_all(&self.word, find, true).chain(implicit)
+    }
+
+    // Suffixes which are not leaves yet. Ukkonen adds leaves
+    // in order of suffixes and the rest ones end at build_info
+    fn implicit_suffixes(&self) -> Range<usize> {
+        let s = &self.build_info;
+        let mut len = if self.tree.is_root(s.node_idx) {
+            0
+        } else {
+            s.edge_pos - self.tree.node(s.node_idx).pos
+        };
+        let mut node_idx = self.tree.node(s.node_idx).parent;
+        while !self.tree.is_root(node_idx) {
+            len += self.tree.node(node_idx).len;
+            node_idx = self.tree.node(node_idx).parent;
+        }
+        self.word.len() - len..self.word.len()
+    }
 }
 
 impl Default for OnlineSuffixTree {
@@ -636,7 +985,412 @@ impl Default for OnlineSuffixTree {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct GeneralizedSuffixTree {
+    // strings one after another, each one followed by '\0'
+    word: String,
+    // starts[i] is start of string i, starts.last() == word.len()
+    starts: Vec<usize>,
+    tree: AloneSuffixTree,
+    // labels[label_starts[i]..label_starts[i + 1]] are (string_idx, offset)
+    // of all suffixes which end in node i
+    label_starts: Vec<usize>,
+    labels: Vec<(usize, usize)>,
+}
+
+impl GeneralizedSuffixTree {
+    /// Construct generalized suffix tree. Complexity O(n)
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "sword", "other"]);
+    /// let gst = GeneralizedSuffixTree::new(vec![String::from("word")]);
+    /// ```
+    /// Every string is terminated by '\0' in the tree.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if some string contains '\0'.
+    pub fn new<I, D>(words: I) -> Self
+    where
+        I: IntoIterator<Item = D>,
+        D: AsRef<str>,
+    {
+        let mut word = String::new();
+        let mut starts = Vec::new();
+        for x in words {
+            let x = x.as_ref();
+            assert!(!x.as_bytes().contains(&0));
+            starts.push(word.len());
+            word.push_str(x);
+            word.push('\0');
+        }
+        starts.push(word.len());
+
+        let mut tree = AloneSuffixTree {
+            nodes: vec![Node {
+                link: Some(NodeIdx::root()),
+                parent: NodeIdx::root(),
+                children: BTreeMap::new(),
+                len: 0,
+                pos: 0,
+            }],
+        };
+        tree.nodes.reserve(2 * word.len() + 1);
+
+        // nodes created while string i is inserted are node_starts[i]..node_starts[i + 1]
+        let mut node_starts = Vec::with_capacity(starts.len());
+        let mut shared = Vec::with_capacity(starts.len() - 1);
+        for bounds in starts.windows(2) {
+            node_starts.push(tree.nodes.len());
+            // string sees the word only up to its own '\0' so leaves end there
+            let cur_word = &word[..bounds[1]];
+            let mut s = State {
+                node_idx: NodeIdx::root(),
+                edge_pos: 0,
+            };
+            for (i, &ch) in cur_word.as_bytes().iter().enumerate().skip(bounds[0]) {
+                while tree
+                    .try_transfer_to(cur_word, &mut s, ch, false)
+                    .if_transfer_not_success(|x| x.create_transfer(i).to_link())
+                {}
+            }
+            // the last suffixes of the string can be equal (with '\0') to suffixes
+            // of previous strings. Then they end in the leaf of the previous string
+            shared.push(if tree.is_root(s.node_idx) {
+                None
+            } else {
+                Some(s.node_idx)
+            });
+        }
+        node_starts.push(tree.nodes.len());
+        tree.nodes.shrink_to_fit();
+
+        let (label_starts, labels) = calc_labels(&starts, &node_starts, &tree, &shared);
+        return Self {
+            word,
+            starts,
+            tree,
+            label_starts,
+            labels,
+        };
+
+        fn calc_labels(
+            starts: &[usize],
+            node_starts: &[usize],
+            tree: &AloneSuffixTree,
+            shared: &[Option<NodeIdx>],
+        ) -> (Vec<usize>, Vec<(usize, usize)>) {
+            let word_len = *starts.last().unwrap();
+            // leaf in which suffix starting at pos ends
+            let mut leaf_of = vec![NodeIdx::root(); word_len];
+            // suffix of the leaf owner
+            let mut suffix_of = vec![0; tree.nodes.len()];
+
+            // Ukkonen creates leaves in order of suffixes and a leaf never
+            // changes its index. Pos of the leaf can't be used, it can point
+            // to the equal substr of another string after split
+            for (i, bounds) in node_starts.windows(2).enumerate() {
+                let mut pos = starts[i];
+                for (idx, node) in tree.nodes[bounds[0]..bounds[1]].iter().enumerate() {
+                    if node.children.is_empty() {
+                        leaf_of[pos] = NodeIdx::new(bounds[0] + idx);
+                        suffix_of[bounds[0] + idx] = pos;
+                        pos += 1;
+                    }
+                }
+                // the shortest suffixes without own leaf repeat suffixes
+                // of the shared leaf owner
+                if let Some(leaf) = shared[i] {
+                    let target = suffix_of[leaf.unwrap()];
+                    for (x, y) in (pos..starts[i + 1]).zip(target..) {
+                        leaf_of[x] = leaf_of[y];
+                    }
+                } else {
+                    debug_assert!(pos == starts[i + 1]);
+                }
+            }
+
+            let mut label_starts = vec![0; tree.nodes.len() + 1];
+            leaf_of
+                .iter()
+                .for_each(|x| label_starts[x.unwrap() + 1] += 1);
+            for i in 1..label_starts.len() {
+                label_starts[i] += label_starts[i - 1];
+            }
+            let mut fill = label_starts.clone();
+            let mut labels = vec![(0, 0); word_len];
+            for (i, bounds) in starts.windows(2).enumerate() {
+                for (offset, leaf) in leaf_of[bounds[0]..bounds[1]].iter().enumerate() {
+                    labels[fill[leaf.unwrap()]] = (i, offset);
+                    fill[leaf.unwrap()] += 1;
+                }
+            }
+            (label_starts, labels)
+        }
+    }
+
+    /// Find substr. Return (string_idx, offset). Complexity O(|find|)
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "sword"]);
+    /// assert_eq!(gst.find("sw"), Some((1, 0)));
+    /// assert_eq!(gst.find("ws"), None);
+    /// ```
+    pub fn find(&self, find: &str) -> Option<(usize, usize)> {
+        let mut res = None;
+        self.for_each_label(self.locus(find)?, |x| {
+            res = Some(x);
+            false
+        });
+        res
+    }
+
+    /// Find all substr. Return sorted (string_idx, offset).
+    /// Complexity O(|find| + occ * log(occ))
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "sword"]);
+    /// assert_eq!(gst.find_all("wor"), vec![(0, 0), (1, 1)]);
+    /// ```
+    pub fn find_all(&self, find: &str) -> Vec<(usize, usize)> {
+        let mut res = Vec::new();
+        if let Some(node_idx) = self.locus(find) {
+            self.for_each_label(node_idx, |x| {
+                res.push(x);
+                true
+            });
+        }
+        res.sort_unstable();
+        res
+    }
+
+    /// Find all strings which contain substr. Each string is reported once.
+    /// Return sorted string indexes. Complexity O(|find| + occ * log(occ))
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "other", "sword"]);
+    /// assert_eq!(gst.find_strings("or"), vec![0, 2]);
+    /// ```
+    pub fn find_strings(&self, find: &str) -> Vec<usize> {
+        let mut res = self
+            .find_all(find)
+            .into_iter()
+            .map(|(string_idx, _)| string_idx)
+            .collect::<Vec<_>>();
+        res.dedup();
+        res
+    }
+
+    /// Number of entries of substr in each string which contains it.
+    /// Return (string_idx, count) sorted by string index. Complexity O(|find| + occ * log(occ))
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["abab", "c", "b"]);
+    /// assert_eq!(gst.string_counts("b"), vec![(0, 2), (2, 1)]);
+    /// ```
+    pub fn string_counts(&self, find: &str) -> Vec<(usize, usize)> {
+        let mut res: Vec<(usize, usize)> = Vec::new();
+        for (string_idx, _) in self.find_all(find) {
+            match res.last_mut() {
+                Some((last, cnt)) if *last == string_idx => *cnt += 1,
+                _ => res.push((string_idx, 1)),
+            }
+        }
+        res
+    }
+
+    /// Longest substr which is contained in at least k strings.
+    /// Complexity O(n * log(n)^2)
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["sandwich", "handwork", "wandering"]);
+    /// assert_eq!(gst.longest_common_substring(2), "andw");
+    /// assert_eq!(gst.longest_common_substring(3), "and");
+    /// assert_eq!(gst.longest_common_substring(4), "");
+    /// ```
+    pub fn longest_common_substring(&self, k: usize) -> &str {
+        // nodes in dfs order, so every child goes after its parent
+        let mut order = Vec::with_capacity(self.tree.nodes.len());
+        let mut depth = vec![0; self.tree.nodes.len()];
+        let mut stack = vec![NodeIdx::root()];
+        while let Some(node_idx) = stack.pop() {
+            order.push(node_idx);
+            for &child in self.node(node_idx).children.values() {
+                depth[child.unwrap()] = depth[node_idx.unwrap()] + self.node(child).len;
+                stack.push(child);
+            }
+        }
+
+        // strings which contain the path of node
+        let mut strings = vec![BTreeSet::new(); self.tree.nodes.len()];
+        let (mut best_len, mut best_end) = (0, 0);
+        for &node_idx in order.iter().rev() {
+            let node = self.node(node_idx);
+            let mut set = BTreeSet::new();
+            for &child in node.children.values() {
+                let mut child_set = core::mem::take(&mut strings[child.unwrap()]);
+                if child_set.len() > set.len() {
+                    core::mem::swap(&mut set, &mut child_set);
+                }
+                set.extend(child_set);
+            }
+            set.extend(
+                self.labels(node_idx)
+                    .iter()
+                    .map(|&(string_idx, _)| string_idx),
+            );
+
+            // root has no edge, it has no children in the tree without strings
+            if !self.is_root(node_idx) && set.len() >= k {
+                // leaf path ends with '\0'
+                let end = node.pos + node.len - if node.children.is_empty() { 1 } else { 0 };
+                let len = depth[node_idx.unwrap()] - (node.pos + node.len - end);
+                if len > best_len {
+                    best_len = len;
+                    best_end = end;
+                }
+            }
+            strings[node_idx.unwrap()] = set;
+        }
+        &self.word[best_end - best_len..best_end]
+    }
+
+    /// Return (string_idx, offset) of all suffixes which end in node.
+    /// Not empty only for leaves
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["ab", "b"]);
+    /// // "b\0" is a suffix of both strings
+    /// let node_idx = gst.try_to_node(NodeIdx::root(), 'b' as u8).unwrap();
+    /// assert_eq!(gst.labels(node_idx), &[(0, 1), (1, 0)]);
+    /// ```
+    #[inline]
+    pub fn labels(&self, node_idx: NodeIdx) -> &[(usize, usize)] {
+        let idx = node_idx.unwrap();
+        &self.labels[self.label_starts[idx]..self.label_starts[idx + 1]]
+    }
+
+    /// Return number of strings
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "sword"]);
+    /// assert_eq!(gst.string_count(), 2);
+    /// ```
+    #[inline]
+    pub fn string_count(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    /// Return ref on string
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "sword"]);
+    /// assert_eq!(gst.string(1), "sword");
+    /// ```
+    #[inline]
+    pub fn string(&self, string_idx: usize) -> &str {
+        &self.word[self.starts[string_idx]..self.starts[string_idx + 1] - 1]
+    }
+
+    /// Return ref on all strings, each one followed by '\0'
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "sword"]);
+    /// assert_eq!(gst.word(), "word\0sword\0");
+    /// ```
+    #[inline]
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// Semantic to check if the node is root
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word"]);
+    /// assert_eq!(gst.is_root(NodeIdx::root()), true);
+    /// ```
+    #[inline]
+    pub fn is_root(&self, node_idx: NodeIdx) -> bool {
+        self.tree.is_root(node_idx)
+    }
+
+    /// Return ref on node in suffix tree
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word"]);
+    /// let node: &Node = gst.node(NodeIdx::root());
+    /// ```
+    #[inline]
+    pub fn node(&self, node_idx: NodeIdx) -> &Node {
+        self.tree.node(node_idx)
+    }
+
+    /// Return ref on root node in suffix tree
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word"]);
+    /// assert_eq!(gst.root_node(), gst.node(NodeIdx::root()));
+    /// ```
+    #[inline]
+    pub fn root_node(&self) -> &Node {
+        &self.tree.nodes[0]
+    }
+
+    /// Go to the next node.
+    /// If there is no transition then return None
+    /// else return node index
+    /// ```
+    /// use suff_collections::tree::*;
+    ///
+    /// let gst = GeneralizedSuffixTree::new(&["word", "sword"]);
+    /// let node: Option<NodeIdx> = gst.try_to_node(NodeIdx::root(), 's' as u8);
+    /// assert!(node.is_some());
+    /// ```
+    #[inline]
+    pub fn try_to_node(&self, current_node: NodeIdx, transition: u8) -> Option<NodeIdx> {
+        self.tree.try_to_node(current_node, transition)
+    }
+
+    // '\0' separates strings, so it can't be a part of match
+    #[inline]
+    fn locus(&self, find: &str) -> Option<NodeIdx> {
+        if find.is_empty() || find.as_bytes().contains(&0) {
+            return None;
+        }
+        let (node_idx, _) = self.tree.locus(&self.word, find, false)?;
+        Some(node_idx)
+    }
+
+    // call f for labels of all leaves in subtree while f returns true
+    fn for_each_label(&self, node_idx: NodeIdx, mut f: impl FnMut((usize, usize)) -> bool) {
+        let mut stack = vec![node_idx];
+        while let Some(node_idx) = stack.pop() {
+            for &x in self.labels(node_idx) {
+                if !f(x) {
+                    return;
+                }
+            }
+            stack.extend(self.node(node_idx).children.values());
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[repr(transparent)]
 pub struct NodeIdx(usize);
 impl NodeIdx {
@@ -670,12 +1424,120 @@ impl NodeIdx {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[repr(transparent)]
 struct AloneSuffixTree {
     nodes: Vec<Node>,
 }
 
 impl AloneSuffixTree {
+    // O(n)
+    // Check that nodes form a tree with edges inside the word, every leaf
+    // is a distinct suffix of the word and every suffix link leads to the node
+    // with the path without the first symbol, so no query can go out of bounds
+    #[cfg(feature = "serde")]
+    fn check(&self, word: &str) -> Result<(), &'static str> {
+        let word = word.as_bytes();
+        let root = match self.nodes.first() {
+            Some(root) => root,
+            None => return Err("tree has no root"),
+        };
+        if !self.is_root(root.parent) || root.len != 0 {
+            return Err("invalid root");
+        }
+
+        let mut visited = vec![false; self.nodes.len()];
+        visited[0] = true;
+        // length of the path from root to the node
+        let mut depths = vec![0; self.nodes.len()];
+        // every suffix of the word with the empty one is at most one leaf
+        let mut leaves = vec![None; word.len() + 1];
+        // nodes in pre-order, so every subtree is a continuous part of it
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![NodeIdx::root()];
+        while let Some(node_idx) = stack.pop() {
+            order.push(node_idx.unwrap());
+            let depth = depths[node_idx.unwrap()];
+            for (&key, &child) in self.node(node_idx).children.iter() {
+                match visited.get_mut(child.unwrap()) {
+                    Some(x) if !*x => *x = true,
+                    _ => return Err("node is not a tree node"),
+                }
+                let node = self.node(child);
+                if node.parent != node_idx {
+                    return Err("invalid parent");
+                }
+                let is_valid_edge = if node.len == 0 {
+                    // terminal leaf
+                    key == 0 && node.children.is_empty() && node.pos <= word.len()
+                } else {
+                    node.pos
+                        .checked_add(node.len)
+                        .is_some_and(|end| end <= word.len())
+                        && word[node.pos] == key
+                };
+                // the path is not longer than the word
+                let depth = match depth + node.len {
+                    x if is_valid_edge && x <= word.len() => x,
+                    _ => return Err("invalid edge"),
+                };
+                if node.children.is_empty() {
+                    // the leaf edge ends at the end of the word, the suffix starts at word.len() - depth
+                    if node.len != 0 && node.pos + node.len != word.len() {
+                        return Err("invalid leaf");
+                    }
+                    match leaves.get_mut(word.len() - depth) {
+                        Some(x) if x.is_none() => *x = Some(child.unwrap()),
+                        _ => return Err("invalid leaf"),
+                    }
+                }
+                depths[child.unwrap()] = depth;
+                stack.push(child);
+            }
+        }
+        if visited.iter().any(|&x| !x) {
+            return Err("node is unreachable");
+        }
+
+        // start of some suffix in the subtree and index of the node after the subtree in order
+        let mut starts = vec![0; self.nodes.len()];
+        let mut position = vec![0; self.nodes.len()];
+        let mut ends = vec![0; self.nodes.len()];
+        order.iter().enumerate().for_each(|(i, &x)| position[x] = i);
+        for &x in order.iter().rev() {
+            if self.nodes[x].children.is_empty() {
+                starts[x] = word.len() - depths[x];
+                ends[x] = position[x] + 1;
+            }
+            let parent = self.nodes[x].parent.unwrap();
+            if x != parent {
+                starts[parent] = starts[x];
+                ends[parent] = ends[parent].max(ends[x]);
+            }
+        }
+        // path of the node is word[starts[x]..starts[x] + depths[x]], the suffix
+        // starts[x] + 1 goes through the link
+        let is_valid_link = |x: usize, link: usize| match depths[x] {
+            _ if link >= self.nodes.len() => false,
+            0 | 1 => link == 0,
+            depth => {
+                depths[link] + 1 == depth
+                    && leaves[starts[x] + 1]
+                        .is_some_and(|leaf| (position[link]..ends[link]).contains(&position[leaf]))
+            }
+        };
+        for (x, node) in self.nodes.iter().enumerate() {
+            if node
+                .link
+                .is_some_and(|link| !is_valid_link(x, link.unwrap()))
+            {
+                return Err("invalid suffix link");
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     fn node(&self, node_idx: NodeIdx) -> &Node {
         &self.nodes[node_idx.unwrap()]
@@ -810,8 +1672,15 @@ impl AloneSuffixTree {
 
     #[inline]
     fn find(&self, word: &str, find: &str, is_online: bool) -> Option<usize> {
+        let (_, edge_pos) = self.locus(word, find, is_online)?;
+        Some(edge_pos - find.len())
+    }
+
+    // Node on which edge the match of find ends and the position
+    // in word right after the match
+    #[inline]
+    fn locus(&self, word: &str, find: &str, is_online: bool) -> Option<(NodeIdx, usize)> {
         let (word, mut find) = (word.as_bytes(), find.as_bytes());
-        let find_len = find.len();
         let mut node_idx = NodeIdx::root();
         loop {
             let node = self.node(node_idx);
@@ -827,7 +1696,7 @@ impl AloneSuffixTree {
             }
 
             if find.is_empty() {
-                return Some(edge_pos - find_len);
+                return Some((node_idx, edge_pos));
             }
             if edge_pos != end_edge_pos {
                 return None;
@@ -835,6 +1704,80 @@ impl AloneSuffixTree {
             node_idx = self.try_to_node(node_idx, find[0])?;
         }
     }
+
+    // Start positions of suffixes which end in leaves under the locus of find.
+    // In online tree suffixes which are not leaves yet are skipped
+    fn find_all<'s>(
+        &'s self,
+        word: &str,
+        find: &str,
+        is_online: bool,
+    ) -> impl Iterator<Item = usize> + 's {
+        let word_len = word.len();
+        // (node, length of path to the node edge)
+        let mut stack = Vec::new();
+        if !find.is_empty() {
+            if let Some((node_idx, _)) = self.locus(word, find, is_online) {
+                let mut depth = 0;
+                let mut parent = self.node(node_idx).parent;
+                while !self.is_root(parent) {
+                    depth += self.node(parent).len;
+                    parent = self.node(parent).parent;
+                }
+                stack.push((node_idx, depth));
+            }
+        }
+
+        iter::from_fn(move || loop {
+            let (node_idx, depth) = stack.pop()?;
+            let node = self.node(node_idx);
+            let len = if is_online && node.len == usize::MAX {
+                word_len - node.pos
+            } else {
+                node.len
+            };
+            if node.children.is_empty() {
+                return Some(word_len - depth - len);
+            }
+            stack.extend(node.children.values().map(|&x| (x, depth + len)));
+        })
+    }
+
+    // number of leaves in subtree of each node
+    fn leaf_counts(&self) -> Vec<usize> {
+        // nodes in dfs order, so every child goes after its parent
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![NodeIdx::root()];
+        while let Some(node_idx) = stack.pop() {
+            order.push(node_idx);
+            stack.extend(self.node(node_idx).children.values());
+        }
+
+        let mut counts = vec![0; self.nodes.len()];
+        for &node_idx in order.iter().rev() {
+            let node = self.node(node_idx);
+            counts[node_idx.unwrap()] = if node.children.is_empty() && !self.is_root(node_idx) {
+                1
+            } else {
+                node.children.values().map(|x| counts[x.unwrap()]).sum()
+            };
+        }
+        counts
+    }
+
+    // number of leaves in subtree of node. O(|subtree|)
+    fn leaf_count(&self, node_idx: NodeIdx) -> usize {
+        let mut cnt = 0;
+        let mut stack = vec![node_idx];
+        while let Some(node_idx) = stack.pop() {
+            let children = &self.node(node_idx).children;
+            if children.is_empty() {
+                cnt += 1;
+            }
+            stack.extend(children.values());
+        }
+        cnt
+    }
 }
 
 enum Transfer<'r, 's, 't> {
@@ -1,4 +1,15 @@
+use self::suff_collections::any_array::*;
 use self::suff_collections::array::*;
+use self::suff_collections::binary::*;
+use self::suff_collections::bwt::*;
+use self::suff_collections::enhanced_array::*;
+use self::suff_collections::error::*;
+use self::suff_collections::fm_index::*;
+use self::suff_collections::gen_array::*;
+use self::suff_collections::int_array::*;
+use self::suff_collections::inverse_array::*;
+use self::suff_collections::lcp::*;
+use self::suff_collections::lz77::*;
 use self::suff_collections::tree::*;
 use rand::{distributions::Alphanumeric, prelude::*};
 use suff_collections;
@@ -91,6 +102,42 @@ fn trust_lcp(line: &str) -> Vec<usize> {
     [0].iter().chain(cmp.iter()).map(|&x| x).collect()
 }
 
+fn to_normal_bytes(line: &[u8]) -> Vec<u8> {
+    // the sentinel is always added to bytes
+    line.iter().chain(&[0]).copied().collect()
+}
+
+fn trust_suffix_array_bytes(line: &[u8]) -> Vec<usize> {
+    if line.is_empty() {
+        return vec![];
+    }
+    let line = to_normal_bytes(line);
+    let mut sa = (0..line.len()).collect::<Vec<_>>();
+    sa.sort_by(|&a, &b| line[a..].cmp(&line[b..]));
+    sa
+}
+
+fn trust_find_all_bytes(line: &[u8], find: &[u8]) -> Vec<usize> {
+    if find.is_empty() {
+        return vec![];
+    }
+    let line = to_normal_bytes(line);
+    if line.len() < find.len() {
+        return vec![];
+    }
+
+    (0..line.len() - find.len())
+        .filter(|&i| line[i..i + find.len()].eq(find))
+        .collect()
+}
+
+fn random_bytes(rng: &mut ThreadRng, cnt: usize) -> Vec<u8> {
+    // small alphabet with zeros to get many repeats and inner sentinels
+    (0..cnt)
+        .map(|_| *[0, 1, 2, 0xfe, 0xff].choose(rng).unwrap())
+        .collect()
+}
+
 #[test]
 fn test_build_ukkonen_and_find() {
     let mut rng = thread_rng();
@@ -548,3 +595,1406 @@ fn test_suffix_array_overflow_4() {
         .owned()
         .to_vec();
 }
+
+#[test]
+fn test_suffix_array_try_new() {
+    let line = "a".repeat(u8::MAX as usize);
+    let err = SuffixError::IndexTooSmall {
+        len: line.len(),
+        max: u8::MAX as usize,
+    };
+    assert_eq!(SuffixArray::<u8>::try_new(&line).unwrap_err(), err);
+    assert_eq!(SuffixArray::<u8>::try_new_compress(&line).unwrap_err(), err);
+    assert_eq!(SuffixArray::<u8>::try_new_stack(&line).unwrap_err(), err);
+    assert_eq!(
+        SuffixArray::<u8>::try_new_stack_compress(&line).unwrap_err(),
+        err
+    );
+    assert_eq!(
+        SuffixArray::<u8>::try_from_bytes(line.as_bytes()).unwrap_err(),
+        err
+    );
+    assert_eq!(
+        SuffixArray::<u8>::try_new("").unwrap_err(),
+        SuffixError::EmptyInput
+    );
+
+    let line = &line[1..];
+    let sa = SuffixArray::<u8>::try_new(line).unwrap();
+    assert_eq!(
+        sa.suffix_array(),
+        SuffixArray::<u8>::new(line).suffix_array()
+    );
+    let lcp = SuffixTree::new(line).try_lcp::<u8>().unwrap();
+    assert_eq!(lcp.inner(), SuffixTree::new(line).lcp::<u8>().inner());
+    assert!(SuffixArray::<u8>::try_from_tree(SuffixTree::new(line)).is_ok());
+
+    let line = "a".repeat(u8::MAX as usize);
+    assert_eq!(
+        SuffixTree::new(&line).try_lcp::<u8>().unwrap_err(),
+        SuffixError::IndexTooSmall {
+            len: line.len(),
+            max: u8::MAX as usize
+        }
+    );
+    assert_eq!(
+        SuffixArray::<u8>::try_from_tree(SuffixTree::new("a\0b")).unwrap_err(),
+        SuffixError::InvalidSentinel { pos: 1 }
+    );
+    assert_eq!(
+        SuffixTree::new("a\0b").try_lcp::<u8>().unwrap_err(),
+        SuffixError::InvalidSentinel { pos: 1 }
+    );
+}
+
+#[test]
+fn test_build_suffix_array_from_bytes() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(1..1024);
+        let line = random_bytes(&mut rng, cnt);
+        let etalon = trust_suffix_array_bytes(&line);
+
+        let res = SuffixArray::<usize>::from_bytes(&line)
+            .suffix_array()
+            .clone();
+        assert_eq!(res, etalon);
+        let res = SuffixArray::<usize>::from_bytes_compress(&line)
+            .suffix_array()
+            .clone();
+        assert_eq!(res, etalon);
+        let res = SuffixArray::<usize>::from_bytes_stack(&line)
+            .suffix_array()
+            .clone();
+        assert_eq!(res, etalon);
+        let res = SuffixArray::<usize>::from_bytes_stack_compress(&line)
+            .suffix_array()
+            .clone();
+        assert_eq!(res, etalon);
+    }
+
+    let line = Vec::new();
+    let res = SuffixArray::<usize>::from_bytes(&line)
+        .suffix_array()
+        .clone();
+    assert_eq!(res, trust_suffix_array_bytes(&line));
+}
+
+#[test]
+fn test_build_suffix_array_from_bytes_and_find_all() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(2..1024);
+        let line = random_bytes(&mut rng, cnt);
+
+        let mut start = rng.gen_range(0..cnt);
+        let mut end = rng.gen_range(0..cnt);
+        if start > end {
+            core::mem::swap(&mut start, &mut end)
+        }
+        let end = core::cmp::min(end, start + 8);
+        let find = &line[start..end];
+
+        let sa = SuffixArray::<usize>::from_bytes(&line);
+        let mut etalon = trust_find_all_bytes(&line, find);
+        etalon.sort();
+
+        let mut res = sa.find_all(find).to_vec();
+        res.sort();
+        assert_eq!(res, etalon);
+
+        let mut res = sa.find_all_big(&sa.lcp(), find).to_vec();
+        res.sort();
+        assert_eq!(res, etalon);
+
+        assert_eq!(sa.find(find).is_some(), !etalon.is_empty());
+    }
+}
+
+#[test]
+fn test_build_suffix_array_from_bytes_and_lcp() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(1..1024);
+        let line = random_bytes(&mut rng, cnt);
+
+        let sa = SuffixArray::<usize>::from_bytes(&line);
+        let lcp = sa.lcp();
+        for (i, (&x, &len)) in sa.iter().zip(lcp.iter()).enumerate().skip(1) {
+            let prev = sa.suffix_array()[i - 1];
+            let etalon = line[x..]
+                .iter()
+                .zip(line[prev..].iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            assert_eq!(len, etalon);
+        }
+    }
+
+    // the sentinel is not equal to a zero byte of the word
+    let sa = SuffixArray::<usize>::from_bytes(b"x\0qx");
+    assert_eq!(
+        sa.lcp().iter().copied().collect::<Vec<_>>(),
+        [0, 0, 0, 0, 1]
+    );
+    // the trailing zero byte is a symbol of the word
+    let sa = SuffixArray::<usize>::from_bytes(&[0x00, 0xff, 0x00]);
+    assert_eq!(sa.word_bytes(), &[0x00, 0xff, 0x00, 0x00]);
+    assert_eq!(sa.find_all(&[0x00]), &[2, 0]);
+}
+
+#[test]
+fn test_build_suffix_array_with_inner_zero() {
+    let line = "a\0b\0a\0ab";
+    let res = SuffixArray::<usize>::new(line).suffix_array().clone();
+    assert_eq!(res, trust_suffix_array(line));
+    let res = SuffixArray::<usize>::new_stack_compress(line)
+        .suffix_array()
+        .clone();
+    assert_eq!(res, trust_suffix_array(line));
+}
+
+#[test]
+fn test_build_int_suffix_array() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(1..1024);
+        let alphabet_size = rng.gen_range(1..100_000);
+        let line = (0..cnt)
+            .map(|_| rng.gen_range(0..alphabet_size) as u32)
+            .collect::<Vec<_>>();
+        let mut normal = line.clone();
+        normal.push(0);
+        let mut etalon = (0..normal.len()).collect::<Vec<_>>();
+        etalon.sort_by(|&a, &b| normal[a..].cmp(&normal[b..]));
+
+        let res = IntSuffixArray::<u32, usize>::new(&line, alphabet_size);
+        assert_eq!(res.suffix_array(), &etalon);
+        let res = IntSuffixArray::<u32, usize>::new_compress(&line, alphabet_size);
+        assert_eq!(res.suffix_array(), &etalon);
+        let res = IntSuffixArray::<u32, usize>::new_stack(&line, alphabet_size);
+        assert_eq!(res.suffix_array(), &etalon);
+        let res = IntSuffixArray::<u32, usize>::new_stack_compress(&line, alphabet_size);
+        assert_eq!(res.suffix_array(), &etalon);
+    }
+
+    let res = IntSuffixArray::<u32, usize>::new(&[], 0);
+    assert!(res.suffix_array().is_empty());
+}
+
+#[test]
+fn test_build_int_suffix_array_and_find_all() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(2..1024);
+        let line = (0..cnt).map(|_| rng.gen_range(0..4u16)).collect::<Vec<_>>();
+
+        let mut start = rng.gen_range(0..cnt);
+        let mut end = rng.gen_range(0..cnt);
+        if start > end {
+            core::mem::swap(&mut start, &mut end)
+        }
+        let end = core::cmp::min(end, start + 8);
+        let find = &line[start..end];
+
+        let sa = IntSuffixArray::<u16, u32>::new(&line, 4);
+        let mut res = sa
+            .find_all(find)
+            .iter()
+            .map(|&x| x as usize)
+            .collect::<Vec<_>>();
+        res.sort();
+        let etalon = if find.is_empty() {
+            vec![]
+        } else {
+            (0..line.len() - find.len() + 1)
+                .filter(|&i| line[i..].starts_with(find))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(res, etalon);
+
+        let lcp = sa.lcp();
+        for i in 1..sa.suffix_array().len() {
+            let (l, r) = (
+                sa.suffix_array()[i - 1] as usize,
+                sa.suffix_array()[i] as usize,
+            );
+            let len = line[l..]
+                .iter()
+                .zip(line[r..].iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            assert_eq!(lcp[i] as usize, len);
+        }
+    }
+}
+
+#[test]
+fn test_int_suffix_array_full_alphabet() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(1..1024);
+        let line = (0..cnt).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let mut normal = line.clone();
+        normal.push(0);
+        let mut etalon = (0..normal.len()).collect::<Vec<_>>();
+        etalon.sort_by(|&a, &b| normal[a..].cmp(&normal[b..]));
+
+        let sa = IntSuffixArray::<u8, usize>::new(&line, 256);
+        assert_eq!(sa.suffix_array(), &etalon);
+    }
+
+    // the trailing 0 is a symbol of the word
+    let sa = IntSuffixArray::<u8, usize>::new(&[5, 0, 7, 0], 256);
+    assert_eq!(sa.word(), &[5, 0, 7, 0, 0]);
+    assert_eq!(sa.find_all(&[7, 0]), &[2]);
+    assert_eq!(
+        sa.lcp().iter().copied().collect::<Vec<_>>(),
+        [0, 0, 1, 0, 0]
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_int_suffix_array_symbol_out_of_alphabet() {
+    let _ = IntSuffixArray::<u32, usize>::new(&[1, 2, 3], 3);
+}
+
+#[test]
+fn test_build_generalized_suffix_array_and_find_all() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt_docs = rng.gen_range(0..16);
+        let docs = (0..cnt_docs)
+            .map(|_| {
+                let cnt = rng.gen_range(0..128);
+                random_bytes(&mut rng, cnt)
+            })
+            .collect::<Vec<_>>();
+        let gsa = GeneralizedSuffixArray::<u32>::new(&docs);
+        assert_eq!(gsa.doc_count(), docs.len());
+
+        let find = &random_bytes(&mut rng, 2);
+        let mut etalon = docs
+            .iter()
+            .enumerate()
+            .flat_map(|(doc_id, doc)| {
+                (0..(doc.len() + 1).saturating_sub(find.len()))
+                    .filter(move |&i| doc[i..].starts_with(find))
+                    .map(move |i| (doc_id, i))
+            })
+            .collect::<Vec<_>>();
+        etalon.sort();
+
+        let mut res = gsa.find_all(find).collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(res, etalon);
+
+        let mut etalon_docs = etalon.iter().map(|&(doc_id, _)| doc_id).collect::<Vec<_>>();
+        etalon_docs.dedup();
+        assert_eq!(gsa.find_docs(find), etalon_docs);
+
+        let etalon_counts = etalon_docs
+            .iter()
+            .map(|&doc_id| (doc_id, etalon.iter().filter(|x| x.0 == doc_id).count()))
+            .collect::<Vec<_>>();
+        assert_eq!(gsa.doc_counts(find), etalon_counts);
+    }
+}
+
+#[test]
+fn test_build_generalized_suffix_array_and_lcp() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt_docs = rng.gen_range(1..16);
+        let docs = (0..cnt_docs)
+            .map(|_| {
+                let cnt = rng.gen_range(0..128);
+                random_bytes(&mut rng, cnt)
+            })
+            .collect::<Vec<_>>();
+        let gsa = GeneralizedSuffixArray::<usize>::new(&docs);
+
+        let suffix = |pos: usize| {
+            let (doc_id, offset) = gsa.doc_pos(pos);
+            &docs[doc_id][offset..]
+        };
+        let sa = gsa.suffix_array();
+        // separators go first in document order from the last one
+        for i in 0..cnt_docs {
+            assert_eq!(
+                gsa.doc_pos(sa[i]),
+                (cnt_docs - 1 - i, docs[cnt_docs - 1 - i].len())
+            );
+        }
+        for i in cnt_docs + 1..sa.len() {
+            assert!(suffix(sa[i - 1]) <= suffix(sa[i]));
+        }
+
+        let lcp = gsa.lcp();
+        for i in 1..sa.len() {
+            let len = suffix(sa[i - 1])
+                .iter()
+                .zip(suffix(sa[i]).iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            assert_eq!(lcp[i], len);
+        }
+    }
+}
+
+#[test]
+fn test_generalized_suffix_array_longest_common_substring() {
+    let mut rng = thread_rng();
+    let contains =
+        |word: &[u8], sub: &[u8]| sub.is_empty() || word.windows(sub.len()).any(|x| x == sub);
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt_docs = rng.gen_range(0..8);
+        let docs = (0..cnt_docs)
+            .map(|_| {
+                let cnt = rng.gen_range(0..32);
+                random_bytes(&mut rng, cnt)
+            })
+            .collect::<Vec<_>>();
+        let gsa = GeneralizedSuffixArray::<u32>::new(&docs);
+
+        for k in 0..=cnt_docs + 1 {
+            let (lcs, entries) = gsa.longest_common_substring(k);
+            let etalon_len = docs
+                .iter()
+                .flat_map(|doc| {
+                    (0..doc.len()).flat_map(move |i| (i + 1..=doc.len()).map(move |j| &doc[i..j]))
+                })
+                .filter(|sub| docs.iter().filter(|x| contains(x, sub)).count() >= k)
+                .map(|sub| sub.len())
+                .max()
+                .unwrap_or(0);
+            assert_eq!(lcs.len(), etalon_len);
+            if !lcs.is_empty() {
+                assert!(docs.iter().filter(|x| contains(x, lcs)).count() >= k);
+            }
+            assert_eq!(entries, gsa.find_all(lcs).collect::<Vec<_>>());
+        }
+
+        if cnt_docs >= 2 {
+            let (a, b) = (&docs[0], &docs[1]);
+            let (lcs, in_a, in_b) = suff_collections::gen_array::longest_common_substring(a, b);
+            let etalon_len = (0..a.len())
+                .flat_map(|i| (i + 1..=a.len()).map(move |j| &a[i..j]))
+                .filter(|sub| contains(b, sub))
+                .map(|sub| sub.len())
+                .max()
+                .unwrap_or(0);
+            assert_eq!(lcs.len(), etalon_len);
+            let positions = |word: &[u8]| {
+                (0..(word.len() + 1).saturating_sub(lcs.len()))
+                    .filter(|&i| !lcs.is_empty() && word[i..].starts_with(lcs))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(in_a, positions(a));
+            assert_eq!(in_b, positions(b));
+        }
+    }
+}
+
+#[test]
+fn test_maximal_unique_matches() {
+    let mut rng = thread_rng();
+    let count = |word: &[u8], sub: &[u8]| word.windows(sub.len()).filter(|&x| x == sub).count();
+
+    for _ in 0..TEST_ITERATIONS {
+        let (cnt_a, cnt_b) = (rng.gen_range(0..32), rng.gen_range(0..32));
+        let a = random_bytes(&mut rng, cnt_a);
+        let b = random_bytes(&mut rng, cnt_b);
+        let min_len = rng.gen_range(0..4);
+
+        let mut etalon = vec![];
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                let len = a[i..]
+                    .iter()
+                    .zip(&b[j..])
+                    .take_while(|(x, y)| x == y)
+                    .count();
+                let sub = &a[i..i + len];
+                if len >= min_len.max(1)
+                    && (i == 0 || j == 0 || a[i - 1] != b[j - 1])
+                    && count(&a, sub) == 1
+                    && count(&b, sub) == 1
+                {
+                    etalon.push((i, j, len));
+                }
+            }
+        }
+        assert_eq!(
+            suff_collections::gen_array::maximal_unique_matches(&a, &b, min_len),
+            etalon
+        );
+    }
+}
+
+fn random_small_str(rng: &mut ThreadRng, cnt: usize) -> String {
+    (0..cnt)
+        .map(|_| *['a', 'b', 'c'].choose(rng).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_build_generalized_suffix_tree_and_find_all() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt_words = rng.gen_range(0..16);
+        let words = (0..cnt_words)
+            .map(|_| {
+                let cnt = rng.gen_range(0..64);
+                random_small_str(&mut rng, cnt)
+            })
+            .collect::<Vec<_>>();
+        let gst = GeneralizedSuffixTree::new(&words);
+        assert_eq!(gst.string_count(), words.len());
+
+        // every suffix of every string ends in exactly one leaf
+        let mut labels = vec![];
+        let mut stack = vec![NodeIdx::root()];
+        while let Some(node_idx) = stack.pop() {
+            labels.extend_from_slice(gst.labels(node_idx));
+            stack.extend(gst.node(node_idx).children().values());
+        }
+        labels.sort();
+        let etalon_labels = words
+            .iter()
+            .enumerate()
+            .flat_map(|(i, word)| (0..=word.len()).map(move |j| (i, j)))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, etalon_labels);
+
+        let cnt = rng.gen_range(1..4);
+        let find = &random_small_str(&mut rng, cnt);
+        let etalon = words
+            .iter()
+            .enumerate()
+            .flat_map(|(i, word)| {
+                (0..(word.len() + 1).saturating_sub(find.len()))
+                    .filter(move |&j| word[j..].starts_with(find.as_str()))
+                    .map(move |j| (i, j))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(gst.find_all(find), etalon);
+        assert_eq!(gst.find(find).is_some(), !etalon.is_empty());
+        if let Some((i, j)) = gst.find(find) {
+            assert!(words[i][j..].starts_with(find.as_str()));
+        }
+
+        let mut etalon_strings = etalon.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        etalon_strings.dedup();
+        assert_eq!(gst.find_strings(find), etalon_strings);
+
+        let etalon_counts = etalon_strings
+            .iter()
+            .map(|&i| (i, etalon.iter().filter(|x| x.0 == i).count()))
+            .collect::<Vec<_>>();
+        assert_eq!(gst.string_counts(find), etalon_counts);
+    }
+}
+
+#[test]
+fn test_generalized_suffix_tree_longest_common_substring() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt_words = rng.gen_range(0..8);
+        let words = (0..cnt_words)
+            .map(|_| {
+                let cnt = rng.gen_range(0..32);
+                random_small_str(&mut rng, cnt)
+            })
+            .collect::<Vec<_>>();
+        let gst = GeneralizedSuffixTree::new(&words);
+
+        for k in 1..=cnt_words + 1 {
+            let res = gst.longest_common_substring(k);
+            assert!(words.iter().filter(|x| x.contains(res)).count() >= k || res.is_empty());
+            let etalon_len = words
+                .iter()
+                .flat_map(|word| {
+                    (0..word.len())
+                        .flat_map(move |i| (i + 1..=word.len()).map(move |j| &word[i..j]))
+                })
+                .filter(|sub| words.iter().filter(|x| x.contains(sub)).count() >= k)
+                .map(|sub| sub.len())
+                .max()
+                .unwrap_or(0);
+            assert_eq!(res.len(), etalon_len);
+        }
+    }
+
+    let gst = GeneralizedSuffixTree::new(Vec::<&str>::new());
+    assert_eq!(gst.longest_common_substring(1), "");
+}
+
+#[test]
+fn test_suffix_tree_matching_statistics() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..256);
+        let word = random_small_str(&mut rng, cnt);
+        let cnt = rng.gen_range(0..256);
+        // 'd' does not occur in the word
+        let query = (0..cnt)
+            .map(|_| *['a', 'b', 'c', 'd'].choose(&mut rng).unwrap())
+            .collect::<String>();
+
+        let etalon = (0..query.len())
+            .map(|i| {
+                (i..=query.len())
+                    .rev()
+                    .find(|&j| word.contains(&query[i..j]))
+                    .unwrap()
+                    - i
+            })
+            .collect::<Vec<_>>();
+
+        let mut ost = OnlineSuffixTree::new();
+        ost.add(&word);
+        // the tree from suffix array has no suffix links
+        let trees = [
+            SuffixTree::new(&word),
+            ost.finish(),
+            SuffixTree::from(SuffixArray::<u32>::new(&word)),
+        ];
+        for st in &trees {
+            let ms = st.matching_statistics(&query);
+            assert_eq!(ms.iter().map(|x| x.0).collect::<Vec<_>>(), etalon);
+            for (i, &(len, pos)) in ms.iter().enumerate() {
+                assert_eq!(&word[pos..pos + len], &query[i..i + len]);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_suffix_array_bwt_and_inverse_bwt() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..1024);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        let (bwt, primary) = sa.bwt();
+
+        let word = sa.word_bytes();
+        assert_eq!(bwt.len(), word.len());
+        for (i, &idx) in sa.iter().enumerate() {
+            let idx = idx as usize;
+            let etalon = if idx == 0 {
+                assert_eq!(primary, i);
+                word[word.len() - 1]
+            } else {
+                word[idx - 1]
+            };
+            assert_eq!(bwt[i], etalon);
+        }
+        assert_eq!(inverse_bwt(&bwt, primary), word);
+    }
+}
+
+#[test]
+fn test_fm_index_count_and_locate() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..1024);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        let sample_rate = rng.gen_range(1..16);
+        let fm = FmIndex::new(&sa, sample_rate);
+
+        for _ in 0..8 {
+            let cnt = rng.gen_range(0..6);
+            let find = random_bytes(&mut rng, cnt);
+            let etalon = sa.find_all(&find);
+            assert_eq!(fm.count(&find), etalon.len());
+            assert_eq!(fm.locate(&find), etalon);
+        }
+        assert_eq!(fm.bwt(), sa.bwt());
+
+        // all 256 bytes take every level of the wavelet matrix
+        let cnt = rng.gen_range(0..2048);
+        let word = (0..cnt).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let sa = SuffixArray::<usize>::from_bytes(&word);
+        let fm = FmIndex::new(&sa, sample_rate);
+        for _ in 0..8 {
+            let cnt = rng.gen_range(0..3);
+            let find = (0..cnt).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+            let etalon = sa.find_all(&find);
+            assert_eq!(fm.count(&find), etalon.len());
+            assert_eq!(fm.locate(&find), etalon);
+        }
+        assert_eq!(fm.bwt(), sa.bwt());
+    }
+}
+
+#[test]
+fn test_count() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..512);
+        let line = random_small_str(&mut rng, cnt);
+
+        let sa = SuffixArray::<u32>::new(&line);
+        let st = SuffixTree::new(&line);
+        let st_from_sa = SuffixTree::from(SuffixArray::<u32>::new(&line));
+        let mut ost = OnlineSuffixTree::new();
+        ost.add(&line);
+        let ost_finish = ost.clone().finish();
+
+        for _ in 0..8 {
+            let cnt = rng.gen_range(0..6);
+            let find = &random_small_str(&mut rng, cnt);
+            let etalon = trust_find_all(&line, find).len();
+            assert_eq!(sa.count(find), etalon);
+            assert_eq!(st.count(find), etalon);
+            assert_eq!(st_from_sa.count(find), etalon);
+            assert_eq!(ost.count(find), etalon);
+            assert_eq!(ost_finish.count(find), etalon);
+        }
+    }
+}
+
+#[test]
+fn test_suffix_tree_find_all() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..512);
+        let line = random_small_str(&mut rng, cnt);
+
+        let st = SuffixTree::new(&line);
+        let st_from_sa = SuffixTree::from(SuffixArray::<u32>::new(&line));
+        let mut ost = OnlineSuffixTree::new();
+        let split = rng.gen_range(0..=line.len());
+        ost.add(&line[..split]);
+        ost.add(&line[split..]);
+        let ost_finish = ost.clone().finish();
+
+        for _ in 0..8 {
+            let cnt = rng.gen_range(0..6);
+            let find = &random_small_str(&mut rng, cnt);
+            let etalon = trust_find_all(&line, find);
+            let sorted = |x: &mut dyn Iterator<Item = usize>| {
+                let mut res = x.collect::<Vec<_>>();
+                res.sort();
+                res
+            };
+            assert_eq!(sorted(&mut st.find_all(find)), etalon);
+            assert_eq!(sorted(&mut st_from_sa.find_all(find)), etalon);
+            assert_eq!(sorted(&mut ost.find_all(find)), etalon);
+            assert_eq!(sorted(&mut ost_finish.find_all(find)), etalon);
+        }
+    }
+}
+
+#[test]
+fn test_suffix_array_range_and_extend() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..512);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+
+        let cnt = rng.gen_range(0..8);
+        let find = random_bytes(&mut rng, cnt);
+        let full = sa.range(&find);
+        assert_eq!(full.count(), sa.count(&find));
+        assert_eq!(sa.positions(full), sa.find_all(&find));
+
+        let mut range = sa.range(&[]);
+        assert_eq!(range.count(), sa.count(&[]));
+        assert!(range.is_empty());
+        let mut pos = 0;
+        while pos < find.len() {
+            let next = rng.gen_range(pos + 1..=find.len());
+            range = sa.extend(range, &find[pos..next]);
+            let etalon = sa.range(&find[..next]);
+            assert_eq!(range.pattern_len(), etalon.pattern_len());
+            assert_eq!(sa.positions(range), sa.positions(etalon));
+            pos = next;
+        }
+        assert_eq!(sa.positions(range), sa.positions(full));
+    }
+}
+
+#[test]
+fn test_any_suffix_array() {
+    let mut rng = thread_rng();
+
+    for &(min, max, size) in &[(0, 254, 1), (255, 260, 2), (65_535, 65_540, 4)] {
+        let cnt = rng.gen_range(min..=max);
+        let line = random_small_str(&mut rng, cnt);
+        let any = AnySuffixArray::new(&line);
+        assert_eq!(any.index_size(), size);
+
+        let sa = SuffixArray::<usize>::new(&line);
+        assert_eq!(any.iter().collect::<Vec<_>>(), sa.suffix_array().to_vec());
+        assert_eq!(
+            any.lcp().iter().collect::<Vec<_>>(),
+            sa.lcp().owned().to_vec()
+        );
+
+        let find = &random_small_str(&mut rng, 3);
+        assert_eq!(any.find(find), sa.find(find));
+        assert_eq!(any.find_all(find).collect::<Vec<_>>(), sa.find_all(find));
+        assert_eq!(any.count(find), sa.count(find));
+
+        let any = AnySuffixArray::from(SuffixTree::new(&line));
+        assert_eq!(any.index_size(), size);
+        assert_eq!(any.iter().collect::<Vec<_>>(), sa.suffix_array().to_vec());
+        let st = SuffixTree::from(any);
+        assert_eq!(
+            st.lcp::<usize>().owned().to_vec(),
+            sa.lcp().owned().to_vec()
+        );
+    }
+}
+
+#[test]
+fn test_lcp_phi() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..1024);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        assert_eq!(sa.lcp_phi().inner(), sa.lcp().inner());
+
+        let line = random_small_str(&mut rng, cnt);
+        let sa = SuffixArray::<usize>::new(&line);
+        assert_eq!(sa.lcp_phi().inner(), sa.lcp().inner());
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_lcp_phi_par() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..1024);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        assert_eq!(sa.lcp_phi_par().inner(), sa.lcp().inner());
+    }
+
+    // several chunks
+    let mut words = vec!["a".repeat(200_000), "abc".repeat(70_000)];
+    for _ in 0..4 {
+        let cnt = rng.gen_range(100_000..300_000);
+        words.push(random_small_str(&mut rng, cnt));
+    }
+    for word in &words {
+        let sa = SuffixArray::<u32>::new(word);
+        assert_eq!(sa.lcp_phi_par().inner(), sa.lcp().inner());
+    }
+}
+
+#[test]
+fn test_lcp_rmq_lce() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(1..512);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        let rmq = LcpRmq::new(&sa, &sa.lcp());
+
+        // the position word.len() is the sentinel
+        for _ in 0..64 {
+            let i = rng.gen_range(0..=word.len());
+            let j = rng.gen_range(0..=word.len());
+            let etalon = if i == j {
+                word.len() - i
+            } else {
+                word[i..]
+                    .iter()
+                    .zip(&word[j..])
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            };
+            assert_eq!(rmq.lce(i, j) as usize, etalon);
+        }
+    }
+
+    // the sentinel does not extend a common zero byte
+    let sa = SuffixArray::<u32>::from_bytes(b"a\0a\0");
+    let rmq = LcpRmq::new(&sa, &sa.lcp());
+    assert_eq!(rmq.lce(1, 3), 1);
+    assert_eq!(rmq.lce(0, 2), 2);
+}
+
+#[test]
+fn test_inverse_suffix_array() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..512);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        let inverse: InverseSuffixArray<u32> = sa.inverse();
+        assert_eq!(inverse.len(), sa.suffix_array().len());
+        for (i, &x) in sa.iter().enumerate() {
+            assert_eq!(inverse.rank(x as usize) as usize, i);
+        }
+
+        let lcp = sa.lcp_with_inverse(&inverse);
+        assert_eq!(lcp.inner(), sa.lcp().inner());
+        if !word.is_empty() {
+            let rmq = LcpRmq::with_inverse(inverse, &lcp);
+            let i = rng.gen_range(0..word.len());
+            let j = rng.gen_range(0..word.len());
+            assert_eq!(rmq.lce(i, j), LcpRmq::new(&sa, &lcp).lce(i, j));
+        }
+    }
+}
+
+#[test]
+fn test_enhanced_suffix_array() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..512);
+        let word = if rng.gen() {
+            random_bytes(&mut rng, cnt)
+        } else {
+            random_small_str(&mut rng, cnt).into_bytes()
+        };
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        let esa = EnhancedSuffixArray::new(sa.clone());
+        let text = &word[..];
+        let suffixes = sa.suffix_array();
+
+        for _ in 0..16 {
+            let cnt = rng.gen_range(0..6);
+            let find = random_bytes(&mut rng, cnt);
+            assert_eq!(esa.find_all(&find), sa.find_all(&find));
+            assert_eq!(esa.count(&find), sa.count(&find));
+        }
+        for (&x, i) in suffixes.iter().zip(0..).skip(1) {
+            let etalon = text[x as usize..]
+                .iter()
+                .zip(&text[suffixes[i - 1] as usize..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            assert_eq!(esa.lcp()[i] as usize, etalon);
+        }
+
+        // the children split the interval and have longer common prefix
+        let mut leaves = 0;
+        let mut stack = esa.root().into_iter().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            if node.is_leaf() {
+                leaves += 1;
+                assert_eq!(node.lcp(), text.len() - suffixes[node.lb()] as usize);
+                continue;
+            }
+            let etalon = (node.lb() + 1..=node.rb())
+                .map(|i| esa.lcp()[i] as usize)
+                .min()
+                .unwrap();
+            assert_eq!(node.lcp(), etalon);
+            let children = esa.children(node).collect::<Vec<_>>();
+            assert!(children.len() >= 2);
+            assert_eq!(children.first().unwrap().lb(), node.lb());
+            assert_eq!(children.last().unwrap().rb(), node.rb());
+            for x in children.windows(2) {
+                assert_eq!(x[0].rb() + 1, x[1].lb());
+            }
+            for x in children {
+                assert!(x.lcp() > node.lcp() || x.is_leaf() && x.lcp() == node.lcp());
+                stack.push(x);
+            }
+        }
+        assert_eq!(leaves, suffixes.len());
+    }
+}
+
+#[test]
+fn test_lcp_bottom_up() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(1..512);
+        let line = random_small_str(&mut rng, cnt);
+        let esa = EnhancedSuffixArray::new(SuffixArray::<u32>::new(&line));
+
+        // internal nodes with internal children from the top-down traversal
+        let mut etalon = vec![];
+        let mut stack = esa.root().into_iter().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            let children = esa
+                .children(node)
+                .filter(|x| !x.is_leaf())
+                .collect::<Vec<_>>();
+            stack.extend(children.iter().copied());
+            etalon.push((node, children));
+        }
+
+        let intervals = esa.bottom_up().collect::<Vec<_>>();
+        for (i, (node, children)) in intervals.iter().enumerate() {
+            // post-order
+            for x in children {
+                assert!(intervals[..i].iter().any(|(y, _)| y == x));
+            }
+            assert!(etalon.contains(&(*node, children.clone())));
+        }
+        assert_eq!(intervals.len(), etalon.len());
+        assert_eq!(intervals.last().map(|x| x.0), esa.root());
+    }
+}
+
+fn trust_maximal_repeats(text: &[u8], min_len: usize) -> Vec<(Vec<u8>, Vec<usize>)> {
+    let n = text.len();
+    let mut res = vec![];
+    for len in min_len.max(1)..n {
+        let mut seen = std::collections::BTreeSet::new();
+        for from in 0..=n - len {
+            let repeat = &text[from..from + len];
+            if !seen.insert(repeat) {
+                continue;
+            }
+            let positions = (0..=n - len)
+                .filter(|&i| &text[i..i + len] == repeat)
+                .collect::<Vec<_>>();
+            let is_diverse =
+                |side: Vec<Option<u8>>| side.iter().any(|x| x.is_none() || *x != side[0]);
+            let left = positions
+                .iter()
+                .map(|&i| i.checked_sub(1).map(|x| text[x]))
+                .collect();
+            let right = positions
+                .iter()
+                .map(|&i| text.get(i + len).copied())
+                .collect();
+            if positions.len() > 1 && is_diverse(left) && is_diverse(right) {
+                res.push((repeat.to_vec(), positions));
+            }
+        }
+    }
+    res.sort();
+    res
+}
+
+#[test]
+fn test_maximal_repeats() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..40);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u8>::from_bytes(&word);
+        let text = &word[..];
+        let min_len = rng.gen_range(0..4);
+
+        let etalon = trust_maximal_repeats(text, min_len);
+        let mut repeats = sa
+            .maximal_repeats(min_len)
+            .map(|x| {
+                let mut positions = x
+                    .positions()
+                    .iter()
+                    .map(|&x| x as usize)
+                    .collect::<Vec<_>>();
+                positions.sort();
+                assert_eq!(positions.len(), x.count());
+                (x.as_bytes().to_vec(), positions)
+            })
+            .collect::<Vec<_>>();
+        repeats.sort();
+        assert_eq!(repeats, etalon);
+
+        let max_len = trust_maximal_repeats(text, 0)
+            .iter()
+            .map(|x| x.0.len())
+            .max();
+        let lrs = sa.longest_repeated_substring();
+        assert_eq!(lrs.as_ref().map(|x| x.as_bytes().len()), max_len);
+        if let Some(lrs) = lrs {
+            for &x in lrs.positions() {
+                assert_eq!(
+                    &text[x as usize..x as usize + lrs.as_bytes().len()],
+                    lrs.as_bytes()
+                );
+            }
+            assert!(lrs.count() > 1);
+        }
+    }
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..512);
+        let line = random_small_str(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::new(&line);
+        let st = SuffixTree::new(&line);
+        let min_len = rng.gen_range(0..8);
+
+        let sa_repeats = sa
+            .maximal_repeats(min_len)
+            .map(|x| {
+                let positions = x
+                    .positions()
+                    .iter()
+                    .map(|&x| x as usize)
+                    .collect::<Vec<_>>();
+                (x.as_bytes(), positions)
+            })
+            .collect::<Vec<_>>();
+        let st_repeats = st
+            .maximal_repeats(min_len)
+            .map(|x| (x.as_bytes(), x.positions().to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(sa_repeats, st_repeats);
+
+        let sa_lrs = sa.longest_repeated_substring();
+        let st_lrs = st.longest_repeated_substring();
+        assert_eq!(
+            sa_lrs.map(|x| (
+                x.as_bytes(),
+                x.positions().iter().map(|&x| x as usize).collect()
+            )),
+            st_lrs.map(|x| (x.as_bytes(), x.positions().to_vec()))
+        );
+    }
+}
+
+#[test]
+fn test_supermaximal_repeats() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..40);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u8>::from_bytes(&word);
+        let text = &word[..];
+        let min_len = rng.gen_range(0..4);
+
+        let maximal = trust_maximal_repeats(text, 0);
+        // (repeat, is supermaximal) of near-supermaximal repeats
+        let mut etalon = vec![];
+        for (repeat, positions) in maximal.iter().filter(|x| x.0.len() >= min_len) {
+            let is_super = maximal
+                .iter()
+                .all(|x| &x.0 == repeat || !x.0.windows(repeat.len()).any(|w| w == &repeat[..]));
+            let is_near = positions.iter().any(|&i| {
+                maximal
+                    .iter()
+                    .filter(|x| &x.0 != repeat)
+                    .all(|(other, other_positions)| {
+                        other_positions
+                            .iter()
+                            .all(|&j| !(j <= i && i + repeat.len() <= j + other.len()))
+                    })
+            });
+            if is_near {
+                etalon.push((repeat.clone(), positions.clone(), is_super));
+            }
+        }
+
+        let mut repeats = sa
+            .supermaximal_repeats(min_len)
+            .map(|(x, is_supermaximal)| {
+                let mut positions = x
+                    .positions()
+                    .iter()
+                    .map(|&x| x as usize)
+                    .collect::<Vec<_>>();
+                positions.sort();
+                (x.as_bytes().to_vec(), positions, is_supermaximal)
+            })
+            .collect::<Vec<_>>();
+        repeats.sort();
+        assert_eq!(repeats, etalon);
+    }
+}
+
+#[test]
+fn test_lz77_factorize() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..256);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        let text = &word[..];
+
+        let factors = sa.lz77_factorize();
+        assert_eq!(lz77_decode(&factors), word);
+
+        let mut i = 0;
+        for factor in factors {
+            // the longest prefix of text[i..] which starts before i
+            let etalon = (0..i)
+                .map(|j| {
+                    text[i..]
+                        .iter()
+                        .zip(&text[j..])
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                })
+                .max()
+                .unwrap_or(0);
+            match factor {
+                Factor::Literal(x) => {
+                    assert_eq!((x, etalon), (text[i], 0));
+                    i += 1;
+                }
+                Factor::Copy { source, len } => {
+                    assert!(source < i);
+                    assert_eq!(len, etalon);
+                    i += len;
+                }
+            }
+        }
+        assert_eq!(i, text.len());
+    }
+
+    // the trailing zero byte is a symbol of the word
+    let word = [7, 0, 7, 0];
+    let factors = SuffixArray::<u32>::from_bytes(&word).lz77_factorize();
+    assert_eq!(lz77_decode(&factors), word);
+}
+
+#[test]
+fn test_binary_format() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..512);
+        let word = random_bytes(&mut rng, cnt);
+        let sa = SuffixArray::<u32>::from_bytes(&word);
+        let lcp = sa.lcp();
+
+        for bytes in [sa.to_binary(None), sa.to_binary(Some(&lcp))] {
+            // copy to the buffer aligned for u32
+            let mut storage = vec![0u8; bytes.len() + 4];
+            let offset = storage.as_ptr().align_offset(4);
+            let buf = &mut storage[offset..offset + bytes.len()];
+            buf.copy_from_slice(&bytes);
+
+            let view = SuffixArrayRef::<u32>::from_bytes(buf).unwrap();
+            assert_eq!(view.word_bytes(), sa.word_bytes());
+            assert_eq!(view.suffix_array(), &sa.suffix_array()[..]);
+            for _ in 0..8 {
+                let cnt = rng.gen_range(0..6);
+                let find = random_bytes(&mut rng, cnt);
+                let etalon = sa.find_all(&find);
+                assert_eq!(view.find_all(&find), etalon);
+                assert_eq!(view.find_all_big(&find), etalon);
+                assert_eq!(view.find(&find), sa.find(&find));
+                assert_eq!(view.find_big(&find), sa.find(&find));
+            }
+
+            assert_eq!(
+                SuffixArrayRef::<u16>::from_bytes(buf).unwrap_err(),
+                FormatError::IndexWidth {
+                    expected: 2,
+                    found: 4
+                }
+            );
+            if !word.is_empty() {
+                let misaligned = &mut storage[offset + 1..offset + 1 + bytes.len()];
+                misaligned.copy_from_slice(&bytes);
+                assert_eq!(
+                    SuffixArrayRef::<u32>::from_bytes(misaligned).unwrap_err(),
+                    FormatError::Misaligned
+                );
+            }
+        }
+
+        if word.is_empty() {
+            continue;
+        }
+        // damaged data
+        let mut bytes = sa.to_binary(Some(&lcp));
+        let i = rng.gen_range(HEADER_LEN..bytes.len());
+        bytes[i] ^= 1;
+        let mut storage = vec![0u8; bytes.len() + 4];
+        let offset = storage.as_ptr().align_offset(4);
+        storage[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        assert_eq!(
+            SuffixArrayRef::<u32>::from_bytes(&storage[offset..offset + bytes.len()]).unwrap_err(),
+            FormatError::ChecksumMismatch
+        );
+        assert_eq!(
+            SuffixArrayRef::<u32>::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            FormatError::Truncated {
+                len: bytes.len() - 1,
+                expected: bytes.len()
+            }
+        );
+        let mut too_large = bytes.clone();
+        too_large[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            SuffixArrayRef::<u32>::from_bytes(&too_large).unwrap_err(),
+            FormatError::TooLarge { len: u64::MAX }
+        );
+        bytes[0] ^= 1;
+        assert_eq!(
+            SuffixArrayRef::<u32>::from_bytes(&bytes).unwrap_err(),
+            FormatError::BadMagic
+        );
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_suffix_array_par() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..1024);
+        let word = random_bytes(&mut rng, cnt);
+        let etalon = SuffixArray::<u32>::from_bytes(&word);
+        let sa = SuffixArray::<u32>::from_bytes_par(&word);
+        assert_eq!(sa.suffix_array(), etalon.suffix_array());
+        assert_eq!(sa.word_bytes(), etalon.word_bytes());
+
+        let line = random_small_str(&mut rng, cnt);
+        let etalon = SuffixArray::<usize>::new(&line);
+        assert_eq!(
+            SuffixArray::<usize>::new_par(&line).suffix_array(),
+            etalon.suffix_array()
+        );
+    }
+
+    // several chunks and blocks, long runs over chunk boundaries
+    let mut words = vec![
+        "a".repeat(200_000),
+        "ab".repeat(100_000),
+        "a".repeat(65_535) + "b" + &"a".repeat(70_000),
+    ];
+    for _ in 0..4 {
+        let cnt = rng.gen_range(100_000..300_000);
+        words.push(random_small_str(&mut rng, cnt));
+    }
+    for word in &words {
+        assert_eq!(
+            SuffixArray::<u32>::new_par(word).suffix_array(),
+            SuffixArray::<u32>::new(word).suffix_array()
+        );
+    }
+    let word = random_bytes(&mut rng, 200_000);
+    assert_eq!(
+        SuffixArray::<u32>::from_bytes_par(&word).suffix_array(),
+        SuffixArray::<u32>::from_bytes(&word).suffix_array()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let mut rng = thread_rng();
+
+    for _ in 0..TEST_ITERATIONS {
+        let cnt = rng.gen_range(0..256);
+        let line = random_small_str(&mut rng, cnt);
+
+        let sa = SuffixArray::<u32>::new(&line);
+        let json = serde_json::to_string(&sa).unwrap();
+        let de: SuffixArray<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(de.word_bytes(), sa.word_bytes());
+        assert_eq!(de.suffix_array(), sa.suffix_array());
+
+        let lcp = sa.lcp();
+        let json = serde_json::to_string(&lcp).unwrap();
+        let de: LCP<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(de.inner(), lcp.inner());
+
+        for st in [SuffixTree::new(&line), SuffixTree::from(sa)] {
+            let json = serde_json::to_string(&st).unwrap();
+            let de: SuffixTree = serde_json::from_str(&json).unwrap();
+            assert_eq!(de.word(), st.word());
+            assert_eq!(de.lcp::<u32>().inner(), st.lcp::<u32>().inner());
+            let find = &random_small_str(&mut rng, 2);
+            assert_eq!(de.count(find), st.count(find));
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_reject_invalid() {
+    let de = serde_json::from_str::<SuffixArray<u32>>(r#"{"word":[98,97,0],"sa":[2,0,1]}"#);
+    assert!(de.is_err());
+    let de = serde_json::from_str::<SuffixArray<u32>>(r#"{"word":[98,97,0],"sa":[2,1,1]}"#);
+    assert!(de.is_err());
+    let de = serde_json::from_str::<SuffixArray<u32>>(r#"{"word":[98,97],"sa":[1,0]}"#);
+    assert!(de.is_err());
+    let de = serde_json::from_str::<SuffixArray<u32>>(r#"{"word":[98,97,0],"sa":[2,1,0]}"#);
+    assert!(de.is_ok());
+
+    let json = serde_json::to_string(&SuffixArray::<u32>::new("banana").lcp()).unwrap();
+    assert!(serde_json::from_str::<LCP<u32>>(&json).is_ok());
+    assert!(serde_json::from_str::<LCP<u32>>("[1,0,0]").is_err());
+    assert!(serde_json::from_str::<LCP<u32>>("[0,0,2]").is_err());
+    assert!(serde_json::from_str::<LCP<u32>>("[0,1,0]").is_ok());
+
+    let st = SuffixTree::new("banana");
+    let json = serde_json::to_string(&st).unwrap();
+    assert!(serde_json::from_str::<SuffixTree>(&json).is_ok());
+    // edge out of the word
+    let bad = json.replacen(r#""len":1,"#, r#""len":100,"#, 1);
+    assert_ne!(bad, json);
+    assert!(serde_json::from_str::<SuffixTree>(&bad).is_err());
+    // child out of the tree
+    let mut bad: serde_json::Value = serde_json::from_str(&json).unwrap();
+    bad["tree"][0]["children"]["98"] = serde_json::json!(1000);
+    assert!(serde_json::from_value::<SuffixTree>(bad).is_err());
+    // cycle instead of the tree
+    let mut bad: serde_json::Value = serde_json::from_str(&json).unwrap();
+    bad["tree"][0]["children"]["98"] = serde_json::json!(0);
+    assert!(serde_json::from_value::<SuffixTree>(bad).is_err());
+    // path from the root is longer than the word, "a" + "na" + "nana"
+    let mut bad: serde_json::Value = serde_json::from_str(&json).unwrap();
+    bad["tree"][2]["pos"] = serde_json::json!(2);
+    bad["tree"][2]["len"] = serde_json::json!(4);
+    assert!(serde_json::from_value::<SuffixTree>(bad).is_err());
+    // leaf which is not a suffix
+    let mut bad: serde_json::Value = serde_json::from_str(&json).unwrap();
+    bad["tree"][2]["pos"] = serde_json::json!(2);
+    assert!(serde_json::from_value::<SuffixTree>(bad).is_err());
+
+    // suffix links of nodes with the same depth are swapped, "ab" -> "d" and "cd" -> "b"
+    let st = SuffixTree::new("abxabycdxcdy");
+    let json = serde_json::to_string(&st).unwrap();
+    let tree: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let nodes = tree["tree"].as_array().unwrap();
+    let node_of = |path: &str| {
+        let mut node = 0;
+        let mut depth = 0;
+        while depth < path.len() {
+            node = nodes[node]["children"][path.as_bytes()[depth].to_string()]
+                .as_u64()
+                .unwrap() as usize;
+            depth += nodes[node]["len"].as_u64().unwrap() as usize;
+        }
+        node
+    };
+    let (ab, cd) = (node_of("ab"), node_of("cd"));
+    assert_eq!(nodes[ab]["link"], serde_json::json!(node_of("b")));
+    assert_eq!(nodes[cd]["link"], serde_json::json!(node_of("d")));
+    let mut bad = tree.clone();
+    bad["tree"][ab]["link"] = nodes[cd]["link"].clone();
+    bad["tree"][cd]["link"] = nodes[ab]["link"].clone();
+    assert!(serde_json::from_value::<SuffixTree>(bad).is_err());
+}